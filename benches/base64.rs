@@ -0,0 +1,56 @@
+use std::io;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use rcli::{decode_stream, encode_stream, Base64Format};
+
+// 16 MiB 的伪随机数据，解码的输入按 base64 -w 76 换行，包含跳过空白的开销
+fn generate_data(len: usize) -> Vec<u8> {
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect()
+}
+
+fn bench_base64_stream(c: &mut Criterion) {
+    let data = generate_data(16 * 1024 * 1024);
+    let mut encoded = Vec::new();
+    encode_stream(&mut data.as_slice(), &mut encoded, Base64Format::Standard)
+        .expect("encode bench data");
+    let wrapped = encoded
+        .chunks(76)
+        .flat_map(|line| line.iter().copied().chain(*b"\n"))
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("base64_stream");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function("encode", |b| {
+        b.iter(|| {
+            encode_stream(
+                &mut data.as_slice(),
+                &mut io::sink(),
+                Base64Format::Standard,
+            )
+            .expect("encode")
+        })
+    });
+    group.bench_function("decode", |b| {
+        b.iter(|| {
+            decode_stream(
+                &mut wrapped.as_slice(),
+                &mut io::sink(),
+                Base64Format::Standard,
+            )
+            .expect("decode")
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_base64_stream);
+criterion_main!(benches);
@@ -0,0 +1,59 @@
+use std::{fmt::Write as _, fs, path::PathBuf};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use rcli::{process_csv, DateColumn, DateOutput, OutputFormat};
+
+// 生成一个足够大的 csv，日期列需要正则 + chrono 解析，能体现 CPU 开销
+fn generate_csv(rows: usize) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("rcli-bench-{}.csv", rows));
+    if path.exists() {
+        return path;
+    }
+    let mut content = String::from("Name,Position,DOB,Nationality,Kit Number\n");
+    for i in 0..rows {
+        let _ = writeln!(
+            content,
+            "Player {i},Forward,\"Apr {}, {} (29)\",Italy,{}",
+            i % 28 + 1,
+            1970 + i % 40,
+            i % 99
+        );
+    }
+    fs::write(&path, content).expect("write bench csv");
+    path
+}
+
+fn bench_process_csv(c: &mut Criterion) {
+    let input = generate_csv(200_000);
+    let inputs = [input.to_string_lossy().into_owned()];
+    let output = std::env::temp_dir().join("rcli-bench-output.json");
+    let output = output.to_str().expect("utf8 temp path");
+    let dates = [DateColumn {
+        column: "DOB".into(),
+        format: "%b %d, %Y".into(),
+    }];
+
+    let mut group = c.benchmark_group("process_csv");
+    group.sample_size(10);
+    for jobs in [1, 0] {
+        let name = if jobs == 1 { "single" } else { "parallel" };
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                process_csv(
+                    &inputs,
+                    output.to_string(),
+                    OutputFormat::Json,
+                    false,
+                    &dates,
+                    DateOutput::Rfc3339,
+                    jobs,
+                )
+                .expect("process csv")
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_process_csv);
+criterion_main!(benches);
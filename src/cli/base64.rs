@@ -1,6 +1,6 @@
 use clap::Parser;
 
-use super::verify_file;
+use super::{verify_file, DecodeOutputOpts};
 
 #[derive(Debug, Parser)]
 pub enum Base64SubCommand {
@@ -19,6 +19,7 @@ pub struct Base64EncodeOpts {
     #[arg(long, default_value = "standard", value_parser = parse_base64_format)]
     pub format: Base64Format,
 }
+// rcli base64 decode -i logo.b64 -o logo.png / rcli base64 decode -i key.b64 --hexdump
 #[derive(Debug, Parser)]
 pub struct Base64DecodeOpts {
     #[arg(short, long, help = "Input file", value_parser = verify_file,  default_value = "-")]
@@ -26,9 +27,12 @@ pub struct Base64DecodeOpts {
 
     #[arg(long, default_value = "standard", value_parser = parse_base64_format)]
     pub format: Base64Format,
+
+    #[command(flatten)]
+    pub output: DecodeOutputOpts,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Base64Format {
     Standard,
     UrlSafe,
@@ -0,0 +1,119 @@
+use std::{fmt, str::FromStr};
+
+use clap::{Args, Parser};
+
+use super::{verify_file, Base64Format};
+
+// rcli encode --codec base58 -i Cargo.toml / rcli decode --codec z85 -i data.z85
+// rcli base64 encode/decode 是 --codec base64 / base64url 的别名
+#[derive(Debug, Parser)]
+pub struct EncodeOpts {
+    #[arg(short, long, help = "Input file", value_parser = verify_file, default_value = "-")]
+    pub input: String,
+
+    #[arg(long, help = "Codec: base64, base64url, hex, hex-upper, base32, base32hex, crockford, base58, base58-flickr, base58check, ascii85, z85", value_parser = parse_codec, default_value = "base64")]
+    pub codec: Codec,
+}
+
+#[derive(Debug, Parser)]
+pub struct DecodeOpts {
+    #[arg(short, long, help = "Input file", value_parser = verify_file, default_value = "-")]
+    pub input: String,
+
+    #[arg(long, help = "Codec: base64, base64url, hex, hex-upper, base32, base32hex, crockford, base58, base58-flickr, base58check, ascii85, z85", value_parser = parse_codec, default_value = "base64")]
+    pub codec: Codec,
+
+    #[command(flatten)]
+    pub output: DecodeOutputOpts,
+}
+
+// 解码结果可能是图片、密钥之类的二进制数据，rcli decode 和 rcli base64 decode 共用
+// rcli decode --codec hex -i key.hex -o key.bin / rcli decode -i key.b64 --hexdump
+#[derive(Debug, Args)]
+pub struct DecodeOutputOpts {
+    #[arg(
+        short,
+        long,
+        help = "Write the raw bytes to a file, - for stdout, without a trailing newline"
+    )]
+    pub output: Option<String>,
+
+    #[arg(long, help = "Write binary output even if stdout is a terminal")]
+    pub force: bool,
+
+    #[arg(
+        long,
+        help = "Preview the decoded bytes as a hexdump",
+        conflicts_with = "output"
+    )]
+    pub hexdump: bool,
+}
+
+/// 支持的编码:
+/// base64, base64url, hex (base16), hex-upper, base32, base32hex, crockford,
+/// base58 (bitcoin), base58-flickr, base58check, ascii85 (base85), z85
+/// base64 和 rcli base64 共用 Base64Format，base64url 就是 Base64(UrlSafe)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Codec {
+    Base64(Base64Format),
+    Hex,
+    HexUpper,
+    Base32,
+    Base32Hex,
+    Crockford,
+    Base58,
+    Base58Flickr,
+    Base58Check,
+    Ascii85,
+    Z85,
+}
+
+fn parse_codec(codec: &str) -> Result<Codec, anyhow::Error> {
+    codec.parse()
+}
+
+impl FromStr for Codec {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "base64" => Ok(Codec::Base64(Base64Format::Standard)),
+            "base64url" => Ok(Codec::Base64(Base64Format::UrlSafe)),
+            "hex" | "base16" => Ok(Codec::Hex),
+            "hex-upper" | "base16-upper" => Ok(Codec::HexUpper),
+            "base32" => Ok(Codec::Base32),
+            "base32hex" => Ok(Codec::Base32Hex),
+            "crockford" | "base32-crockford" => Ok(Codec::Crockford),
+            "base58" | "base58-bitcoin" => Ok(Codec::Base58),
+            "base58-flickr" => Ok(Codec::Base58Flickr),
+            "base58check" => Ok(Codec::Base58Check),
+            "ascii85" | "base85" => Ok(Codec::Ascii85),
+            "z85" => Ok(Codec::Z85),
+            _ => Err(anyhow::anyhow!("Invalid codec")),
+        }
+    }
+}
+
+impl From<Codec> for &'static str {
+    fn from(codec: Codec) -> Self {
+        match codec {
+            Codec::Base64(Base64Format::Standard) => "base64",
+            Codec::Base64(Base64Format::UrlSafe) => "base64url",
+            Codec::Hex => "hex",
+            Codec::HexUpper => "hex-upper",
+            Codec::Base32 => "base32",
+            Codec::Base32Hex => "base32hex",
+            Codec::Crockford => "crockford",
+            Codec::Base58 => "base58",
+            Codec::Base58Flickr => "base58-flickr",
+            Codec::Base58Check => "base58check",
+            Codec::Ascii85 => "ascii85",
+            Codec::Z85 => "z85",
+        }
+    }
+}
+
+impl fmt::Display for Codec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
@@ -1,34 +1,129 @@
 use clap::Parser;
-use std::{fmt, str::FromStr};
+use std::{fmt, path::PathBuf, str::FromStr};
 
 // use crate::cli::verify_input_file;
-use super::verify_file;
+use super::{verify_file, verify_input_pattern, verify_path};
 
+// args_conflicts_with_subcommands: `rcli csv -i ...` 直接转换，`rcli csv mask ...` 走子命令
+// subcommand_negates_reqs: 使用子命令时不再要求 --input
 #[derive(Debug, Parser)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub struct CsvOpts {
-    #[arg(short, long, help = "Input csv file", value_parser = verify_file)]
-    pub input: String,
+    #[command(subcommand)]
+    pub cmd: Option<CsvSubCommand>,
+
+    // 可以传多个文件或 glob: -i a.csv b.csv / -i 'data/*.csv'
+    // 没有子命令时 clap 会要求必填，Vec 在使用子命令时为空
+    #[arg(short, long, help = "Input csv, yaml or jsonl files, or glob patterns", value_parser = verify_input_pattern, num_args = 1.., required = true)]
+    pub input: Vec<String>,
 
-    #[arg(short, long, help = "Output json file")]
+    #[arg(short, long, help = "Output json file", conflicts_with = "out_dir")]
     // 字面量转化为 String
     pub output: Option<String>,
 
-    #[arg(long, help = "Output format", value_parser = parse_format, default_value = "json")]
+    #[arg(long, help = "Write one output per input into this directory", value_parser = verify_path)]
+    pub out_dir: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Overwrite existing files in --out-dir",
+        requires = "out_dir"
+    )]
+    pub force: bool,
+
+    #[arg(long, help = "Output format: json, jsonl or yaml", value_parser = parse_format, default_value = "json")]
     pub format: OutputFormat,
 
+    #[arg(long, help = "Emit one yaml document per row, only for yaml output")]
+    pub yaml_stream: bool,
+
     #[arg(long, help = "Has header", default_value_t = true)]
     pub header: bool,
 
     #[arg(short, long, help = "Delimiter", default_value_t = ',')]
     pub delimiter: char,
+
+    // 可以重复传入多次: --parse-date "DOB=%b %d, %Y" --parse-date "Joined=%Y-%m-%d"
+    #[arg(long, help = "Parse date column, eg: DOB=%b %d, %Y", value_parser = parse_date_column)]
+    pub parse_date: Vec<DateColumn>,
+
+    #[arg(long, help = "Date output format: rfc3339 or unix", value_parser = parse_date_output, default_value = "rfc3339")]
+    pub date_output: DateOutput,
+
+    #[arg(
+        short,
+        long,
+        help = "Worker threads, 0 means all cores",
+        default_value_t = 1
+    )]
+    pub jobs: usize,
+}
+
+#[derive(Debug, Parser)]
+pub enum CsvSubCommand {
+    #[command(name = "mask", about = "Mask sensitive columns in a csv or json file")]
+    Mask(CsvMaskOpts),
+}
+
+#[derive(Debug, Parser)]
+pub struct CsvMaskOpts {
+    #[arg(short, long, help = "Input csv or json file", value_parser = verify_file)]
+    pub input: String,
+
+    #[arg(short, long, help = "Output file, default masked.csv / masked.json")]
+    pub output: Option<String>,
+
+    #[arg(
+        long,
+        help = "Columns to mask, eg: email,phone",
+        value_delimiter = ',',
+        required = true
+    )]
+    pub columns: Vec<String>,
+
+    #[arg(long, help = "Mask mode: hash, redact, fake or partial", value_parser = parse_mask_mode, default_value = "hash")]
+    pub mode: MaskMode,
+
+    #[arg(short, long, help = "Black3 key file, required by hash and fake", value_parser = verify_file)]
+    pub key: Option<String>,
+
+    #[arg(
+        short,
+        long,
+        help = "Worker threads, 0 means all cores",
+        default_value_t = 1
+    )]
+    pub jobs: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum MaskMode {
+    Hash,
+    Redact,
+    Fake,
+    Partial,
 }
 
 #[derive(Debug, Clone, Copy)]
 pub enum OutputFormat {
     Json,
+    Jsonl,
     Yaml,
 }
 
+/// 需要做日期解析的列，以及对应的 strftime 格式
+#[derive(Debug, Clone)]
+pub struct DateColumn {
+    pub column: String,
+    pub format: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum DateOutput {
+    Rfc3339,
+    Unix,
+}
+
 fn parse_format(format: &str) -> Result<OutputFormat, anyhow::Error> {
     // .parse() 会自动使用为 OutputFormat 实现的 FromStr（见下面的 impl FromStr for OutputFormat）
     format.parse()
@@ -43,6 +138,7 @@ impl FromStr for OutputFormat {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "json" => Ok(OutputFormat::Json),
+            "jsonl" => Ok(OutputFormat::Jsonl),
             "yaml" => Ok(OutputFormat::Yaml),
             _ => Err(anyhow::anyhow!("Invalid format")),
         }
@@ -54,6 +150,7 @@ impl From<OutputFormat> for &'static str {
     fn from(format: OutputFormat) -> Self {
         match format {
             OutputFormat::Json => "json",
+            OutputFormat::Jsonl => "jsonl",
             OutputFormat::Yaml => "yaml",
         }
     }
@@ -66,3 +163,93 @@ impl fmt::Display for OutputFormat {
         write!(f, "{}", Into::<&str>::into(*self))
     }
 }
+
+// "DOB=%b %d, %Y" -> DateColumn { column: "DOB", format: "%b %d, %Y" }
+// 用 split_once 只切第一个 '='，格式串里面允许再出现 '='
+fn parse_date_column(s: &str) -> Result<DateColumn, anyhow::Error> {
+    match s.split_once('=') {
+        Some((column, format)) if !column.is_empty() && !format.is_empty() => Ok(DateColumn {
+            column: column.to_string(),
+            format: format.to_string(),
+        }),
+        _ => Err(anyhow::anyhow!("Invalid date column, expected col=format")),
+    }
+}
+
+fn parse_mask_mode(mode: &str) -> Result<MaskMode, anyhow::Error> {
+    mode.parse()
+}
+
+impl FromStr for MaskMode {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hash" => Ok(MaskMode::Hash),
+            "redact" => Ok(MaskMode::Redact),
+            "fake" => Ok(MaskMode::Fake),
+            "partial" => Ok(MaskMode::Partial),
+            _ => Err(anyhow::anyhow!("Invalid mask mode")),
+        }
+    }
+}
+
+impl From<MaskMode> for &'static str {
+    fn from(mode: MaskMode) -> Self {
+        match mode {
+            MaskMode::Hash => "hash",
+            MaskMode::Redact => "redact",
+            MaskMode::Fake => "fake",
+            MaskMode::Partial => "partial",
+        }
+    }
+}
+
+impl fmt::Display for MaskMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
+
+fn parse_date_output(format: &str) -> Result<DateOutput, anyhow::Error> {
+    format.parse()
+}
+
+impl FromStr for DateOutput {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rfc3339" => Ok(DateOutput::Rfc3339),
+            "unix" => Ok(DateOutput::Unix),
+            _ => Err(anyhow::anyhow!("Invalid date output format")),
+        }
+    }
+}
+
+impl From<DateOutput> for &'static str {
+    fn from(format: DateOutput) -> Self {
+        match format {
+            DateOutput::Rfc3339 => "rfc3339",
+            DateOutput::Unix => "unix",
+        }
+    }
+}
+
+impl fmt::Display for DateOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date_column() {
+        let col = parse_date_column("DOB=%b %d, %Y").unwrap();
+        assert_eq!(col.column, "DOB");
+        assert_eq!(col.format, "%b %d, %Y");
+        assert!(parse_date_column("DOB").is_err());
+        assert!(parse_date_column("=%Y").is_err());
+    }
+}
@@ -0,0 +1,106 @@
+use std::{fmt, str::FromStr};
+
+use clap::Parser;
+
+use super::{verify_file, SeedOpts};
+
+#[derive(Debug, Parser)]
+pub enum GenSubCommand {
+    #[command(about = "Generate a random token or id")]
+    Token(GenTokenOpts),
+
+    #[command(about = "Generate a prefixed API key with a checksum")]
+    Apikey(GenApiKeyOpts),
+}
+
+#[derive(Debug, Parser)]
+pub struct GenTokenOpts {
+    #[arg(long, help = "Token format: hex, b64url, uuid4, uuid7, ulid or nanoid", value_parser = parse_token_format, default_value = "hex")]
+    pub format: TokenFormat,
+
+    #[arg(long, help = "Random bytes for hex and b64url", default_value_t = 32)]
+    pub bytes: usize,
+
+    #[arg(short, long, help = "Length of nanoid", default_value_t = 21)]
+    pub length: usize,
+
+    #[command(flatten)]
+    pub seed: SeedOpts,
+}
+
+// rcli gen apikey --prefix rk_live / rcli gen apikey verify -i keys.txt
+#[derive(Debug, Parser)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct GenApiKeyOpts {
+    #[command(subcommand)]
+    pub cmd: Option<ApiKeySubCommand>,
+
+    #[arg(long, help = "Key prefix, eg: rk_live", default_value = "rk")]
+    pub prefix: String,
+
+    #[arg(long, help = "Length of the random base62 body", default_value_t = 30)]
+    pub length: usize,
+
+    #[command(flatten)]
+    pub seed: SeedOpts,
+}
+
+#[derive(Debug, Parser)]
+pub enum ApiKeySubCommand {
+    #[command(about = "Verify API key checksums offline, one key per line")]
+    Verify(ApiKeyVerifyOpts),
+}
+
+#[derive(Debug, Parser)]
+pub struct ApiKeyVerifyOpts {
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenFormat {
+    Hex,
+    B64Url,
+    Uuid4,
+    Uuid7,
+    Ulid,
+    Nanoid,
+}
+
+fn parse_token_format(format: &str) -> Result<TokenFormat, anyhow::Error> {
+    format.parse()
+}
+
+impl FromStr for TokenFormat {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hex" => Ok(TokenFormat::Hex),
+            "b64url" => Ok(TokenFormat::B64Url),
+            "uuid4" => Ok(TokenFormat::Uuid4),
+            "uuid7" => Ok(TokenFormat::Uuid7),
+            "ulid" => Ok(TokenFormat::Ulid),
+            "nanoid" => Ok(TokenFormat::Nanoid),
+            _ => Err(anyhow::anyhow!("Invalid token format")),
+        }
+    }
+}
+
+impl From<TokenFormat> for &'static str {
+    fn from(format: TokenFormat) -> Self {
+        match format {
+            TokenFormat::Hex => "hex",
+            TokenFormat::B64Url => "b64url",
+            TokenFormat::Uuid4 => "uuid4",
+            TokenFormat::Uuid7 => "uuid7",
+            TokenFormat::Ulid => "ulid",
+            TokenFormat::Nanoid => "nanoid",
+        }
+    }
+}
+
+impl fmt::Display for TokenFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
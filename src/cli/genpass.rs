@@ -1,19 +1,270 @@
-use clap::Parser;
+use std::{fmt, str::FromStr};
+
+use clap::{ArgAction, Args, Parser};
+
+use super::{verify_file, SeedOpts};
 
 #[derive(Debug, Parser)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct GenPassOpts {
+    #[command(subcommand)]
+    pub cmd: Option<GenPassSubCommand>,
+
     #[arg(short, long, default_value_t = 16)]
     pub length: u8,
 
-    #[arg(long, default_value_t = true)]
+    // rcli genpass --entropy 128: 按字符集自动算出需要的最短长度
+    #[arg(long, help = "Pick the shortest length reaching this many bits of entropy", conflicts_with_all = ["length", "passphrase", "pattern", "mode", "pin"])]
+    pub entropy: Option<f64>,
+
+    #[command(flatten)]
+    pub classes: CharsetOpts,
+
+    // 自定义字符集，设置后不再使用上面四类字符
+    #[arg(long, help = "Use only these characters", allow_hyphen_values = true, conflicts_with_all = ["symbols", "include_ambiguous", "min_upper", "min_lower", "min_digits", "min_symbols"])]
+    pub charset: Option<String>,
+
+    #[arg(
+        long,
+        help = "Override the symbol set, eg: '!#%+-'",
+        allow_hyphen_values = true
+    )]
+    pub symbols: Option<String>,
+
+    #[arg(
+        long,
+        help = "Characters to exclude",
+        default_value = "",
+        allow_hyphen_values = true
+    )]
+    pub exclude: String,
+
+    #[arg(long, help = "Allow look-alike characters I, O, l and 0")]
+    pub include_ambiguous: bool,
+
+    #[arg(long, help = "Minimum uppercase letters [default: 1]")]
+    pub min_upper: Option<usize>,
+
+    #[arg(long, help = "Minimum lowercase letters [default: 1]")]
+    pub min_lower: Option<usize>,
+
+    #[arg(long, help = "Minimum digits [default: 1]")]
+    pub min_digits: Option<usize>,
+
+    #[arg(long, help = "Minimum symbols [default: 1]")]
+    pub min_symbols: Option<usize>,
+
+    #[arg(long, help = "Password policy file, eg: policies/aws.yaml", value_parser = verify_file, conflicts_with_all = ["passphrase", "charset"])]
+    pub policy: Option<String>,
+
+    // rcli genpass --mode pronounceable -l 10 / rcli genpass --pattern 'Cvccvc-99-Cvccvc'
+    // 这两种模式每次只生成一个密码，不支持批量输出和策略文件
+    #[arg(long, help = "Generation mode: random or pronounceable", value_parser = parse_genpass_mode, default_value = "random", conflicts_with_all = ["passphrase", "policy", "charset", "count", "unique", "format"])]
+    pub mode: GenPassMode,
+
+    #[arg(long, help = "Template: C/c consonant, V/v vowel, A/a letter, 9 digit, # symbol", allow_hyphen_values = true, conflicts_with_all = ["mode", "passphrase", "policy", "charset", "count", "unique", "format"])]
+    pub pattern: Option<String>,
+
+    // 数字 PIN: rcli genpass --pin --length 6，会避开 123456、000000、生日这类弱 PIN
+    #[arg(long, help = "Generate a numeric PIN, rejecting weak patterns", conflicts_with_all = ["passphrase", "pattern", "policy", "charset", "mode"])]
+    pub pin: bool,
+
+    // 批量生成: rcli genpass --count 100 --unique --format csv
+    #[arg(
+        short,
+        long,
+        help = "Number of passwords to generate",
+        default_value_t = 1,
+        conflicts_with = "passphrase"
+    )]
+    pub count: usize,
+
+    #[arg(
+        long,
+        help = "Make sure generated passwords are distinct",
+        conflicts_with = "passphrase"
+    )]
+    pub unique: bool,
+
+    #[arg(long, help = "Output format: plain, json or csv (with zxcvbn score)", value_parser = parse_genpass_format, default_value = "plain")]
+    pub format: GenPassFormat,
+
+    #[arg(
+        long,
+        help = "Print password, entropy_bits, zxcvbn_score and crack_time as JSON",
+        conflicts_with_all = ["format", "pin"]
+    )]
+    pub json: bool,
+
+    // 密码短语模式: rcli genpass --passphrase --words 6 --separator - --capitalize --add-digit
+    #[arg(long, help = "Generate a diceware passphrase instead of a password")]
+    pub passphrase: bool,
+
+    #[arg(
+        long,
+        help = "Number of words in the passphrase",
+        default_value_t = 6,
+        requires = "passphrase"
+    )]
+    pub words: u8,
+
+    #[arg(
+        long,
+        help = "Separator between words",
+        default_value = "-",
+        requires = "passphrase",
+        allow_hyphen_values = true
+    )]
+    pub separator: String,
+
+    #[arg(long, help = "Capitalize each word", requires = "passphrase")]
+    pub capitalize: bool,
+
+    #[arg(
+        long,
+        help = "Append a digit to a random word",
+        requires = "passphrase"
+    )]
+    pub add_digit: bool,
+
+    #[arg(long, help = "Custom wordlist file, one word per line", value_parser = verify_file, requires = "passphrase")]
+    pub wordlist: Option<String>,
+
+    #[command(flatten)]
+    pub seed: SeedOpts,
+}
+
+// 默认四类字符都启用，用 --no-xxx 关闭，genpass、genpass derive 和 vault gen 共用
+// ArgAction::SetFalse: 出现该参数时设置为 false，不出现时默认为 true
+#[derive(Debug, Clone, Copy, Args)]
+pub struct CharsetOpts {
+    #[arg(long = "no-uppercase", help = "Exclude uppercase letters", action = ArgAction::SetFalse)]
     pub uppercase: bool,
 
-    #[arg(long, default_value_t = true)]
+    #[arg(long = "no-lowercase", help = "Exclude lowercase letters", action = ArgAction::SetFalse)]
     pub lowercase: bool,
 
-    #[arg(long, default_value_t = true)]
+    #[arg(long = "no-number", help = "Exclude numbers", action = ArgAction::SetFalse)]
     pub number: bool,
 
-    #[arg(long, default_value_t = true)]
+    #[arg(long = "no-symbol", help = "Exclude symbols", action = ArgAction::SetFalse)]
     pub symbol: bool,
 }
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GenPassMode {
+    Random,
+    Pronounceable,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GenPassFormat {
+    Plain,
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Parser)]
+pub enum GenPassSubCommand {
+    #[command(about = "Check passwords against a policy, one password per line")]
+    Check(GenPassCheckOpts),
+
+    #[command(about = "Derive a site password from a master passphrase")]
+    Derive(GenPassDeriveOpts),
+}
+
+#[derive(Debug, Parser)]
+pub struct GenPassCheckOpts {
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+
+    #[arg(long, value_parser = verify_file)]
+    pub policy: String,
+}
+
+// 主密码默认从终端读取（不回显），也可以用 --master-file 指定文件
+#[derive(Debug, Parser)]
+pub struct GenPassDeriveOpts {
+    #[arg(long)]
+    pub site: String,
+
+    #[arg(long)]
+    pub login: String,
+
+    #[arg(long, help = "Increase to rotate the password", default_value_t = 1)]
+    pub counter: u32,
+
+    #[arg(long, help = "Read the master passphrase from a file", value_parser = verify_file)]
+    pub master_file: Option<String>,
+
+    #[arg(short, long, default_value_t = 16)]
+    pub length: u8,
+
+    #[command(flatten)]
+    pub classes: CharsetOpts,
+
+    #[arg(long, help = "Password policy file, eg: policies/aws.yaml", value_parser = verify_file)]
+    pub policy: Option<String>,
+}
+
+fn parse_genpass_mode(mode: &str) -> Result<GenPassMode, anyhow::Error> {
+    mode.parse()
+}
+
+impl FromStr for GenPassMode {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(GenPassMode::Random),
+            "pronounceable" => Ok(GenPassMode::Pronounceable),
+            _ => Err(anyhow::anyhow!("Invalid genpass mode")),
+        }
+    }
+}
+
+impl From<GenPassMode> for &'static str {
+    fn from(mode: GenPassMode) -> Self {
+        match mode {
+            GenPassMode::Random => "random",
+            GenPassMode::Pronounceable => "pronounceable",
+        }
+    }
+}
+
+impl fmt::Display for GenPassMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
+
+fn parse_genpass_format(format: &str) -> Result<GenPassFormat, anyhow::Error> {
+    format.parse()
+}
+
+impl FromStr for GenPassFormat {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(GenPassFormat::Plain),
+            "json" => Ok(GenPassFormat::Json),
+            "csv" => Ok(GenPassFormat::Csv),
+            _ => Err(anyhow::anyhow!("Invalid genpass format")),
+        }
+    }
+}
+
+impl From<GenPassFormat> for &'static str {
+    fn from(format: GenPassFormat) -> Self {
+        match format {
+            GenPassFormat::Plain => "plain",
+            GenPassFormat::Json => "json",
+            GenPassFormat::Csv => "csv",
+        }
+    }
+}
+
+impl fmt::Display for GenPassFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
@@ -1,11 +1,17 @@
 mod base64;
+mod codec;
 mod csv;
+mod gen;
 mod genpass;
+mod otp;
+mod pass;
+mod secret;
 mod text;
+mod vault;
 
 use std::path::{Path, PathBuf};
 
-use clap::Parser;
+use clap::{Args, Parser};
 
 // use crate::cli::csv::CsvOpts;
 // use self::csv::CsvOpts;
@@ -13,12 +19,18 @@ use clap::Parser;
 // - self ：当前模块
 // - super ：父模块
 // - crate ：当前 crate 的根模块
-use self::{csv::CsvOpts, genpass::GenPassOpts};
 
 pub use self::{
     base64::{Base64Format, Base64SubCommand},
-    csv::OutputFormat,
-    text::{TextSignFormat, TextSubCommand},
+    codec::{Codec, DecodeOpts, DecodeOutputOpts, EncodeOpts},
+    csv::{CsvOpts, CsvSubCommand, DateColumn, DateOutput, MaskMode, OutputFormat},
+    gen::{ApiKeySubCommand, GenApiKeyOpts, GenSubCommand, TokenFormat},
+    genpass::{CharsetOpts, GenPassFormat, GenPassMode, GenPassOpts, GenPassSubCommand},
+    otp::{OtpAlgorithm, OtpSubCommand},
+    pass::PassSubCommand,
+    secret::{SecretSubCommand, ShareFormat},
+    text::{KeyEncoding, TextSignFormat, TextSubCommand},
+    vault::{VaultOpts, VaultSubCommand},
 };
 
 #[derive(Debug, Parser)]
@@ -40,13 +52,47 @@ pub enum Subcommand {
     // 用户输入 rcli csv ... 或 rcli genpass ...
     // - 带 subcommand 的：
     // 用户输入 rcli base64 encode ... 或 rcli base64 decode ... （Base64SubCommand 内部还有更多子命令）
+    #[command(subcommand, about = "Generate tokens and ids")]
+    Gen(GenSubCommand),
+
+    #[command(subcommand, about = "TOTP / HOTP one-time passwords")]
+    Otp(OtpSubCommand),
+
+    #[command(subcommand, about = "Check password strength")]
+    Pass(PassSubCommand),
+
+    #[command(
+        subcommand,
+        about = "Split and combine secrets with Shamir's secret sharing"
+    )]
+    Secret(SecretSubCommand),
+
+    #[command(name = "vault", about = "Encrypted local password vault")]
+    Vault(VaultOpts),
+
     #[command(subcommand)]
     Base64(Base64SubCommand),
 
+    #[command(name = "encode", about = "Encode bytes with a text codec")]
+    Encode(EncodeOpts),
+
+    #[command(name = "decode", about = "Decode text produced by a codec")]
+    Decode(DecodeOpts),
+
     #[command(subcommand)]
     Text(TextSubCommand),
 }
 
+/// --seed: 固定随机数种子得到可复现的输出，生成密码、token、密钥的命令共用
+#[derive(Debug, Clone, Copy, Args)]
+pub struct SeedOpts {
+    #[arg(
+        long,
+        help = "Seed the RNG for reproducible output (insecure, tests and demos only)"
+    )]
+    pub seed: Option<u64>,
+}
+
 /// 验证文件是否存在
 fn verify_file(file_name: &str) -> Result<String, &'static str> {
     // 判断input 是 - 或者文件存在
@@ -60,6 +106,17 @@ fn verify_file(file_name: &str) -> Result<String, &'static str> {
     }
 }
 
+/// 验证输入是存在的文件，或者是一个 glob 模式（包含 * ? [）
+fn verify_input_pattern(pattern: &str) -> Result<String, &'static str> {
+    if pattern.contains(['*', '?', '[']) {
+        glob::Pattern::new(pattern)
+            .map(|_| pattern.into())
+            .map_err(|_| "Invalid glob pattern")
+    } else {
+        verify_file(pattern)
+    }
+}
+
 fn verify_path(path: &str) -> Result<PathBuf, &'static str> {
     let path = Path::new(path);
     if path.exists() && path.is_dir() {
@@ -86,4 +143,18 @@ mod tests {
         assert_eq!(verify_file("Cargo.toml"), Ok("Cargo.toml".into()));
         assert_eq!(verify_file("not-exist"), Err("File does not exist"));
     }
+
+    #[test]
+    fn test_verify_input_pattern() {
+        assert_eq!(verify_input_pattern("Cargo.toml"), Ok("Cargo.toml".into()));
+        assert_eq!(
+            verify_input_pattern("assets/*.csv"),
+            Ok("assets/*.csv".into())
+        );
+        assert_eq!(verify_input_pattern("[a"), Err("Invalid glob pattern"));
+        assert_eq!(
+            verify_input_pattern("not-exist"),
+            Err("File does not exist")
+        );
+    }
 }
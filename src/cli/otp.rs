@@ -0,0 +1,137 @@
+use std::{fmt, str::FromStr};
+
+use clap::Parser;
+
+use super::SeedOpts;
+
+// rcli otp new --issuer rcli --account me@example.com
+// rcli otp generate --secret JBSWY3DPEHPK3PXP
+// rcli otp verify --secret JBSWY3DPEHPK3PXP --code 123456
+#[derive(Debug, Parser)]
+pub enum OtpSubCommand {
+    #[command(about = "Generate a TOTP code, or a HOTP code with --counter")]
+    Generate(OtpGenerateOpts),
+
+    #[command(about = "Verify a TOTP or HOTP code")]
+    Verify(OtpVerifyOpts),
+
+    #[command(about = "Create a new secret and its otpauth:// URI")]
+    New(OtpNewOpts),
+}
+
+#[derive(Debug, Parser)]
+pub struct OtpGenerateOpts {
+    #[arg(long, help = "Base32 encoded secret")]
+    pub secret: String,
+
+    #[arg(long, help = "Hash algorithm: sha1, sha256 or sha512", value_parser = parse_otp_algorithm, default_value = "sha1")]
+    pub algorithm: OtpAlgorithm,
+
+    #[arg(long, default_value_t = 6, value_parser = clap::value_parser!(u32).range(6..=8))]
+    pub digits: u32,
+
+    #[arg(long, help = "TOTP time step in seconds", default_value_t = 30)]
+    pub period: u64,
+
+    #[arg(long, help = "Unix time to use instead of now")]
+    pub time: Option<u64>,
+
+    #[arg(long, help = "HOTP counter, generates a HOTP code instead of TOTP", conflicts_with_all = ["time", "period"])]
+    pub counter: Option<u64>,
+}
+
+#[derive(Debug, Parser)]
+pub struct OtpVerifyOpts {
+    #[arg(long, help = "Base32 encoded secret")]
+    pub secret: String,
+
+    #[arg(long)]
+    pub code: String,
+
+    #[arg(long, help = "Hash algorithm: sha1, sha256 or sha512", value_parser = parse_otp_algorithm, default_value = "sha1")]
+    pub algorithm: OtpAlgorithm,
+
+    #[arg(long, default_value_t = 6, value_parser = clap::value_parser!(u32).range(6..=8))]
+    pub digits: u32,
+
+    #[arg(long, help = "TOTP time step in seconds", default_value_t = 30)]
+    pub period: u64,
+
+    #[arg(long, help = "Unix time to use instead of now")]
+    pub time: Option<u64>,
+
+    #[arg(long, help = "HOTP counter, verifies a HOTP code instead of TOTP", conflicts_with_all = ["time", "period"])]
+    pub counter: Option<u64>,
+
+    // 窗口越大越容易被暴力猜中，也会让校验循环变长，最多前后 10 步
+    #[arg(
+        long,
+        help = "Accept codes this many steps before or after (0-10)",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u64).range(0..=10)
+    )]
+    pub window: u64,
+}
+
+#[derive(Debug, Parser)]
+pub struct OtpNewOpts {
+    #[arg(long, default_value = "rcli")]
+    pub issuer: String,
+
+    #[arg(long, help = "Account name, eg: me@example.com")]
+    pub account: String,
+
+    #[arg(long, help = "Hash algorithm: sha1, sha256 or sha512", value_parser = parse_otp_algorithm, default_value = "sha1")]
+    pub algorithm: OtpAlgorithm,
+
+    #[arg(long, default_value_t = 6, value_parser = clap::value_parser!(u32).range(6..=8))]
+    pub digits: u32,
+
+    #[arg(long, help = "TOTP time step in seconds", default_value_t = 30)]
+    pub period: u64,
+
+    #[arg(long, help = "Secret size in bytes", default_value_t = 20)]
+    pub bytes: usize,
+
+    #[command(flatten)]
+    pub seed: SeedOpts,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OtpAlgorithm {
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+fn parse_otp_algorithm(algorithm: &str) -> Result<OtpAlgorithm, anyhow::Error> {
+    algorithm.parse()
+}
+
+impl FromStr for OtpAlgorithm {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "sha1" => Ok(OtpAlgorithm::Sha1),
+            "sha256" => Ok(OtpAlgorithm::Sha256),
+            "sha512" => Ok(OtpAlgorithm::Sha512),
+            _ => Err(anyhow::anyhow!("Invalid otp algorithm")),
+        }
+    }
+}
+
+impl From<OtpAlgorithm> for &'static str {
+    fn from(algorithm: OtpAlgorithm) -> Self {
+        match algorithm {
+            OtpAlgorithm::Sha1 => "SHA1",
+            OtpAlgorithm::Sha256 => "SHA256",
+            OtpAlgorithm::Sha512 => "SHA512",
+        }
+    }
+}
+
+impl fmt::Display for OtpAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
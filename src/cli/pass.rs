@@ -0,0 +1,29 @@
+use clap::Parser;
+
+use super::verify_file;
+
+#[derive(Debug, Parser)]
+pub enum PassSubCommand {
+    #[command(about = "Audit password strength, one password per line")]
+    Check(PassCheckOpts),
+}
+
+#[derive(Debug, Parser)]
+pub struct PassCheckOpts {
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+
+    // 用户名、公司名之类的词，出现在密码里会降低强度
+    #[arg(
+        long,
+        help = "Extra dictionary words, eg: alice,acme",
+        value_delimiter = ','
+    )]
+    pub user_inputs: Vec<String>,
+
+    #[arg(long, help = "Exit with an error if any score is below this (0-4)", value_parser = clap::value_parser!(u8).range(0..=4))]
+    pub min_score: Option<u8>,
+
+    #[arg(long, help = "Output json")]
+    pub json: bool,
+}
@@ -0,0 +1,82 @@
+use std::{fmt, path::PathBuf, str::FromStr};
+
+use clap::Parser;
+
+use super::{verify_file, verify_path};
+
+// rcli secret split -i fixtures/ed25519.sk --shares 5 --threshold 3 -o shares/
+// rcli secret combine -i shares/share-1.txt -i shares/share-3.txt -i shares/share-5.txt
+#[derive(Debug, Parser)]
+pub enum SecretSubCommand {
+    #[command(about = "Split a secret into shares, any threshold of them can recover it")]
+    Split(SecretSplitOpts),
+
+    #[command(about = "Recover a secret from its shares")]
+    Combine(SecretCombineOpts),
+}
+
+#[derive(Debug, Parser)]
+pub struct SecretSplitOpts {
+    #[arg(short, long, help = "Password or key file to split", value_parser = verify_file, default_value = "-")]
+    pub input: String,
+
+    #[arg(short = 'n', long, help = "Number of shares to create", value_parser = clap::value_parser!(u8).range(2..))]
+    pub shares: u8,
+
+    #[arg(short, long, help = "Shares needed to recover the secret", value_parser = clap::value_parser!(u8).range(2..))]
+    pub threshold: u8,
+
+    #[arg(long, help = "Share encoding: base64 or hex", value_parser = parse_share_format, default_value = "base64")]
+    pub format: ShareFormat,
+
+    #[arg(short, long, help = "Write share-N.txt files into this directory", value_parser = verify_path)]
+    pub output: Option<PathBuf>,
+
+    #[arg(long, help = "Overwrite existing share-N.txt files in --output", requires = "output")]
+    pub force: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct SecretCombineOpts {
+    #[arg(short, long, help = "Files with one or more shares, one per line", value_parser = verify_file, default_value = "-")]
+    pub input: Vec<String>,
+
+    #[arg(short, long, help = "Write the secret to a file instead of stdout")]
+    pub output: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShareFormat {
+    Base64,
+    Hex,
+}
+
+fn parse_share_format(format: &str) -> Result<ShareFormat, anyhow::Error> {
+    format.parse()
+}
+
+impl FromStr for ShareFormat {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "base64" => Ok(ShareFormat::Base64),
+            "hex" => Ok(ShareFormat::Hex),
+            _ => Err(anyhow::anyhow!("Invalid share format")),
+        }
+    }
+}
+
+impl From<ShareFormat> for &'static str {
+    fn from(format: ShareFormat) -> Self {
+        match format {
+            ShareFormat::Base64 => "base64",
+            ShareFormat::Hex => "hex",
+        }
+    }
+}
+
+impl fmt::Display for ShareFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
@@ -7,7 +7,7 @@ use std::{
 use anyhow::{anyhow, Error, Result};
 use clap::Parser;
 
-use super::{verify_file, verify_path};
+use super::{verify_file, verify_path, SeedOpts};
 
 #[derive(Debug, Parser)]
 pub enum TextSubCommand {
@@ -19,6 +19,9 @@ pub enum TextSubCommand {
 
     #[command(about = "generate a key pair")]
     Generate(TextGenerateKeyOpts),
+
+    #[command(about = "re-encode a legacy black3 key with a versioned header")]
+    Migrate(TextMigrateKeyOpts),
 }
 
 #[derive(Debug, Parser)]
@@ -54,6 +57,33 @@ pub struct TextGenerateKeyOpts {
 
     #[arg(short, long, value_parser = verify_path)]
     pub output: PathBuf,
+
+    #[arg(long, help = "Black3 key encoding: raw, hex or base64", default_value = "hex", value_parser = parse_key_encoding)]
+    pub encoding: KeyEncoding,
+
+    #[command(flatten)]
+    pub seed: SeedOpts,
+}
+
+// 旧的 black3 key 是 32 个可打印字符，迁移之后 key 本身不变，已有的签名仍然有效
+// 想要完整的 256 bit 熵需要用 text generate 重新生成
+#[derive(Debug, Parser)]
+pub struct TextMigrateKeyOpts {
+    #[arg(short, long, value_parser = verify_file)]
+    pub key: String,
+
+    #[arg(short, long)]
+    pub output: String,
+
+    #[arg(long, help = "Key encoding: raw, hex or base64", default_value = "hex", value_parser = parse_key_encoding)]
+    pub encoding: KeyEncoding,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyEncoding {
+    Raw,
+    Hex,
+    Base64,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -92,3 +122,36 @@ impl Display for TextSignFormat {
         write!(f, "{}", Into::<&'static str>::into(*self))
     }
 }
+
+fn parse_key_encoding(encoding: &str) -> Result<KeyEncoding> {
+    encoding.parse()
+}
+
+impl FromStr for KeyEncoding {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "raw" => Ok(KeyEncoding::Raw),
+            "hex" => Ok(KeyEncoding::Hex),
+            "base64" => Ok(KeyEncoding::Base64),
+            _ => Err(anyhow!("Invalid key encoding")),
+        }
+    }
+}
+
+impl From<KeyEncoding> for &'static str {
+    fn from(encoding: KeyEncoding) -> Self {
+        match encoding {
+            KeyEncoding::Raw => "raw",
+            KeyEncoding::Hex => "hex",
+            KeyEncoding::Base64 => "base64",
+        }
+    }
+}
+
+impl Display for KeyEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Into::<&'static str>::into(*self))
+    }
+}
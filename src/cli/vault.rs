@@ -0,0 +1,103 @@
+use clap::Parser;
+
+use super::{verify_file, CharsetOpts};
+
+// rcli vault init / rcli vault gen github -l 24 / rcli vault get github
+// 主密码默认从终端读取（不回显），也可以用 --master-file 指定文件
+#[derive(Debug, Parser)]
+pub struct VaultOpts {
+    #[command(subcommand)]
+    pub cmd: VaultSubCommand,
+
+    #[arg(long, help = "Vault file", default_value = "rcli.vault", global = true)]
+    pub file: String,
+
+    #[arg(long, help = "Read the master password from a file", value_parser = verify_file, global = true)]
+    pub master_file: Option<String>,
+}
+
+#[derive(Debug, Parser)]
+pub enum VaultSubCommand {
+    #[command(about = "Create a new encrypted vault")]
+    Init,
+
+    #[command(about = "Store a password, read from the terminal or --password-file")]
+    Add(VaultAddOpts),
+
+    #[command(about = "Print a stored password to stdout")]
+    Get(VaultGetOpts),
+
+    #[command(about = "List entry names")]
+    List,
+
+    #[command(about = "Remove an entry")]
+    Rm(VaultRmOpts),
+
+    #[command(about = "Generate a password and store it in one step")]
+    Gen(VaultGenOpts),
+
+    #[command(about = "Export all entries as plaintext JSON")]
+    Export(VaultExportOpts),
+}
+
+#[derive(Debug, Parser)]
+pub struct VaultAddOpts {
+    pub name: String,
+
+    #[arg(long, help = "Read the password from a file", value_parser = verify_file)]
+    pub password_file: Option<String>,
+
+    #[arg(short, long)]
+    pub username: Option<String>,
+
+    #[arg(long)]
+    pub url: Option<String>,
+
+    #[arg(long)]
+    pub notes: Option<String>,
+
+    #[arg(long, help = "Replace an existing entry")]
+    pub force: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct VaultGetOpts {
+    pub name: String,
+
+    #[arg(long, help = "Print the whole entry as JSON")]
+    pub json: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct VaultRmOpts {
+    pub name: String,
+}
+
+#[derive(Debug, Parser)]
+pub struct VaultGenOpts {
+    pub name: String,
+
+    #[arg(short, long, default_value_t = 16)]
+    pub length: u8,
+
+    #[command(flatten)]
+    pub classes: CharsetOpts,
+
+    #[arg(short, long)]
+    pub username: Option<String>,
+
+    #[arg(long)]
+    pub url: Option<String>,
+
+    #[arg(long)]
+    pub notes: Option<String>,
+
+    #[arg(long, help = "Replace an existing entry")]
+    pub force: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct VaultExportOpts {
+    #[arg(short, long, help = "Write to a file instead of stdout")]
+    pub output: Option<String>,
+}
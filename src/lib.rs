@@ -2,7 +2,14 @@ mod cli;
 mod process;
 mod utils;
 
-pub use cli::{Base64Format, Base64SubCommand, Opts, Subcommand, TextSignFormat, TextSubCommand};
+pub use cli::{
+    ApiKeySubCommand, Base64Format, Base64SubCommand, CharsetOpts, Codec, CsvOpts, CsvSubCommand,
+    DateColumn, DateOutput, DecodeOpts, DecodeOutputOpts, EncodeOpts, GenApiKeyOpts, GenPassFormat,
+    GenPassMode, GenPassOpts, GenPassSubCommand, GenSubCommand, KeyEncoding, MaskMode, Opts,
+    OtpAlgorithm, OtpSubCommand, OutputFormat, PassSubCommand, SecretSubCommand, SeedOpts,
+    ShareFormat, Subcommand, TextSignFormat, TextSubCommand, TokenFormat, VaultOpts,
+    VaultSubCommand,
+};
 
 pub use process::*;
 pub use utils::*;
@@ -1,21 +1,60 @@
-use std::fs;
+use std::{
+    fs,
+    io::{IsTerminal, Write},
+    path::{Path, PathBuf},
+};
 
 use clap::Parser;
+use rand::RngCore;
 
 use rcli::{
-    process_csv, process_decode, process_encode, process_genpass, process_text_generate_keye,
-    process_text_sign, process_text_verify, Base64SubCommand, Opts, Subcommand, TextSignFormat,
-    TextSubCommand,
+    build_rng, entropy_label, expand_inputs, format_passwords, generate_apikey,
+    generate_otp_secret, generate_token, hexdump, load_wordlist, password_report,
+    process_apikey_verify, process_codec_decode, process_codec_encode, process_csv,
+    process_csv_each, process_csv_mask, process_genpass, process_genpass_batch,
+    process_genpass_derive, process_otp_generate, process_otp_verify, process_pass_check,
+    process_passphrase, process_pattern, process_pin, process_policy_check, process_secret_combine,
+    process_secret_split, process_text_generate_keye, process_text_migrate_key, process_text_sign,
+    process_text_verify, pronounceable_pattern, read_secret, write_private, ApiKeySubCommand,
+    Base64SubCommand, Codec, CsvOpts, CsvSubCommand, DecodeOutputOpts, GenApiKeyOpts,
+    GenPassConfig, GenPassFormat, GenPassMode, GenPassOpts, GenPassSubCommand, GenSubCommand, Opts,
+    OtpConfig, OtpSubCommand, PassSubCommand, PasswordPolicy, SecretSubCommand, SeedOpts,
+    StrengthReport, Subcommand, TextSignFormat, TextSubCommand, Vault, VaultEntry, VaultSubCommand,
 };
 use zxcvbn::zxcvbn;
 
 /// cargo add clap --features derive (只使用这个feature，clap：https://docs.rs/clap/latest/clap/_derive/_tutorial/index.html)
 fn main() -> anyhow::Result<()> {
     // cargo run csv --input assets/juventus.csv
+    // 不再打印 {:?} 调试信息: otp --secret 等参数会把密钥明文输出到终端
     let options = Opts::parse();
-    println!("{:?}", options);
     match options.cmd {
         // 调试eg: cargo run csv --input assets/juventus.csv --format yaml
+        // eg: cargo run csv -i 'assets/*.csv' --out-dir /tmp
+        // eg: cargo run csv -i assets/juventus.csv --format yaml --yaml-stream
+        // eg: cargo run csv -i assets/juventus.csv --parse-date "DOB=%b %d, %Y" --date-output unix
+        // eg: cargo run csv mask -i users.csv --columns email,phone --mode fake -k fixtures/black3.txt
+        Subcommand::Csv(CsvOpts {
+            cmd: Some(CsvSubCommand::Mask(opts)),
+            ..
+        }) => {
+            let output = opts.output.unwrap_or_else(|| {
+                let ext = Path::new(&opts.input)
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .unwrap_or("csv");
+                format!("masked.{}", ext)
+            });
+            process_csv_mask(
+                &opts.input,
+                &output,
+                &opts.columns,
+                opts.mode,
+                opts.key.as_deref(),
+                opts.jobs,
+            )?;
+        }
+
         Subcommand::Csv(opts) => {
             let output = if let Some(output) = opts.output {
                 output.clone()
@@ -30,43 +69,430 @@ fn main() -> anyhow::Result<()> {
                 // 使用 From for &'static str 完成到字符串的转换
                 format!("output.{}", opts.format)
             };
-            process_csv(&opts.input, output, opts.format)?;
+            let inputs = expand_inputs(&opts.input)?;
+            // 指定了 --out-dir 时每个输入单独输出，否则合并到一个文件
+            if let Some(out_dir) = opts.out_dir {
+                process_csv_each(
+                    &inputs,
+                    &out_dir,
+                    opts.format,
+                    opts.yaml_stream,
+                    &opts.parse_date,
+                    opts.date_output,
+                    opts.jobs,
+                    opts.force,
+                )?;
+            } else {
+                process_csv(
+                    &inputs,
+                    output,
+                    opts.format,
+                    opts.yaml_stream,
+                    &opts.parse_date,
+                    opts.date_output,
+                    opts.jobs,
+                )?;
+            }
         }
 
         // 调试eg: cargo run genpass --length 16
+        // eg: cargo run genpass --symbols '-_' --exclude 'xyz' --min-digits 3
+        // eg: cargo run genpass --passphrase --words 6 --capitalize --add-digit
+        // eg: cargo run genpass derive --site example.com --login me --counter 1
+        Subcommand::GenPass(GenPassOpts {
+            cmd: Some(GenPassSubCommand::Derive(opts)),
+            ..
+        }) => {
+            let master = read_secret("Master passphrase: ", opts.master_file.as_deref())?;
+            let config = GenPassConfig {
+                upper: opts.classes.uppercase,
+                lower: opts.classes.lowercase,
+                number: opts.classes.number,
+                symbol: opts.classes.symbol,
+                ..GenPassConfig::new(opts.length)
+            };
+            let policy = opts.policy.map(PasswordPolicy::load).transpose()?;
+            let pwd = process_genpass_derive(
+                &master,
+                &opts.site,
+                &opts.login,
+                opts.counter,
+                &config,
+                policy.as_ref(),
+            )?;
+            println!("{}", pwd);
+        }
+
+        // eg: cargo run genpass check --policy policies/aws.yaml -i passwords.txt
+        Subcommand::GenPass(GenPassOpts {
+            cmd: Some(GenPassSubCommand::Check(opts)),
+            ..
+        }) => {
+            let policy = PasswordPolicy::load(&opts.policy)?;
+            let results = process_policy_check(&opts.input, &policy)?;
+            let failed = results.iter().filter(|v| !v.is_empty()).count();
+            // 只输出行号，避免把密码打印到终端
+            for (i, violations) in results.iter().enumerate() {
+                if violations.is_empty() {
+                    println!("line {}: ok", i + 1);
+                } else {
+                    println!("line {}: {}", i + 1, violations.join("; "));
+                }
+            }
+            if failed > 0 {
+                return Err(anyhow::anyhow!(
+                    "{} of {} passwords violate the policy",
+                    failed,
+                    results.len()
+                ));
+            }
+        }
+
+        Subcommand::GenPass(opts) if opts.passphrase => {
+            let wordlist = load_wordlist(opts.wordlist.as_deref())?;
+            let (pass, entropy) = process_passphrase(
+                &wordlist,
+                opts.words,
+                &opts.separator,
+                opts.capitalize,
+                opts.add_digit,
+                &mut seeded_rng(opts.seed),
+            )?;
+            print_password("生成密码短语", &pass, entropy, opts.json)?;
+        }
+
+        // eg: cargo run genpass --pin --length 6
+        Subcommand::GenPass(opts) if opts.pin => {
+            let (pins, avoided) = process_pin(
+                opts.length,
+                opts.count,
+                opts.unique,
+                &mut seeded_rng(opts.seed),
+            )?;
+            if opts.count == 1 && opts.format == GenPassFormat::Plain {
+                println!("生成 PIN: {}", pins[0]);
+            } else {
+                println!("{}", format_passwords(&pins, opts.format)?);
+            }
+            // 报告被丢弃的候选命中了哪些弱模式
+            let avoided = avoided
+                .iter()
+                .map(|(pattern, n)| format!("{} x{}", pattern, n))
+                .collect::<Vec<_>>();
+            if !avoided.is_empty() {
+                eprintln!("已避开: {}", avoided.join(", "));
+            }
+        }
+
+        // eg: cargo run genpass --mode pronounceable -l 10
+        // eg: cargo run genpass --pattern 'Cvccvc-99-Cvccvc'
+        Subcommand::GenPass(opts)
+            if opts.pattern.is_some() || opts.mode == GenPassMode::Pronounceable =>
+        {
+            let pattern = opts
+                .pattern
+                .unwrap_or_else(|| pronounceable_pattern(opts.length));
+            let (pwd, entropy) = process_pattern(&pattern, &mut seeded_rng(opts.seed))?;
+            print_password("生成密码", &pwd, entropy, opts.json)?;
+        }
+
         Subcommand::GenPass(opts) => {
-            let pwd = process_genpass(
+            let mut config = GenPassConfig {
+                length: opts.length,
+                upper: opts.classes.uppercase,
+                lower: opts.classes.lowercase,
+                number: opts.classes.number,
+                symbol: opts.classes.symbol,
+                charset: opts.charset,
+                symbols: opts.symbols,
+                exclude: opts.exclude,
+                include_ambiguous: opts.include_ambiguous,
+                min_upper: opts.min_upper,
+                min_lower: opts.min_lower,
+                min_digits: opts.min_digits,
+                min_symbols: opts.min_symbols,
+            };
+            // eg: cargo run genpass --entropy 128
+            if let Some(bits) = opts.entropy {
+                config.length = config.length_for_entropy(bits)?;
+            }
+            // eg: cargo run genpass --policy policies/aws.yaml
+            // eg: cargo run genpass --count 100 --unique --format csv
+            let policy = opts.policy.map(PasswordPolicy::load).transpose()?;
+            // 策略会调整长度和最少次数，熵按调整之后的配置计算
+            let mut effective = config.clone();
+            if let Some(policy) = &policy {
+                policy.apply(&mut effective)?;
+            }
+            let entropy = effective.entropy()?;
+            let passwords = process_genpass_batch(
+                &config,
+                policy.as_ref(),
+                opts.count,
+                opts.unique,
+                &mut seeded_rng(opts.seed),
+            )?;
+            if opts.count == 1 && (opts.json || opts.format == GenPassFormat::Plain) {
+                print_password("生成密码", &passwords[0], entropy, opts.json)?;
+            } else if opts.json {
+                let reports = passwords
+                    .iter()
+                    .map(|pwd| password_report(pwd, entropy))
+                    .collect::<Vec<_>>();
+                println!("{}", serde_json::to_string_pretty(&reports)?);
+            } else {
+                println!("{}", format_passwords(&passwords, opts.format)?);
+            }
+        }
+
+        // eg: cargo run -- gen apikey verify -i keys.txt
+        Subcommand::Gen(GenSubCommand::Apikey(GenApiKeyOpts {
+            cmd: Some(ApiKeySubCommand::Verify(opts)),
+            ..
+        })) => {
+            let results = process_apikey_verify(&opts.input)?;
+            let failed = results.iter().filter(|r| r.is_some()).count();
+            // 和 genpass check 一样只输出行号，不回显 key
+            for (i, err) in results.iter().enumerate() {
+                match err {
+                    None => println!("line {}: ok", i + 1),
+                    Some(err) => println!("line {}: {}", i + 1, err),
+                }
+            }
+            if failed > 0 {
+                return Err(anyhow::anyhow!(
+                    "{} of {} keys are malformed",
+                    failed,
+                    results.len()
+                ));
+            }
+        }
+
+        // eg: cargo run -- gen apikey --prefix rk_live
+        Subcommand::Gen(GenSubCommand::Apikey(opts)) => {
+            let key = generate_apikey(&opts.prefix, opts.length, &mut seeded_rng(opts.seed))?;
+            println!("{}", key);
+        }
+
+        // eg: cargo run -- gen token --format b64url --bytes 32
+        // eg: cargo run -- gen token --format uuid7
+        Subcommand::Gen(GenSubCommand::Token(opts)) => {
+            let token = generate_token(
+                opts.format,
+                opts.bytes,
                 opts.length,
-                opts.uppercase,
-                opts.lowercase,
-                opts.number,
-                opts.symbol,
+                &mut seeded_rng(opts.seed),
+            )?;
+            println!("{}", token);
+        }
+
+        // eg: cargo run -- otp new --account me@example.com
+        Subcommand::Otp(OtpSubCommand::New(opts)) => {
+            let config = OtpConfig {
+                algorithm: opts.algorithm,
+                digits: opts.digits,
+                period: opts.period,
+            };
+            let (secret, uri) = generate_otp_secret(
+                &opts.issuer,
+                &opts.account,
+                &config,
+                opts.bytes,
+                &mut seeded_rng(opts.seed),
+            )?;
+            println!("secret: {}", secret);
+            println!("uri: {}", uri);
+        }
+
+        // eg: cargo run -- otp generate --secret JBSWY3DPEHPK3PXP
+        Subcommand::Otp(OtpSubCommand::Generate(opts)) => {
+            let config = OtpConfig {
+                algorithm: opts.algorithm,
+                digits: opts.digits,
+                period: opts.period,
+            };
+            let code = process_otp_generate(&opts.secret, &config, opts.time, opts.counter)?;
+            println!("{}", code);
+        }
+
+        Subcommand::Otp(OtpSubCommand::Verify(opts)) => {
+            let config = OtpConfig {
+                algorithm: opts.algorithm,
+                digits: opts.digits,
+                period: opts.period,
+            };
+            let valid = process_otp_verify(
+                &opts.secret,
+                &opts.code,
+                &config,
+                opts.time,
+                opts.counter,
+                opts.window,
             )?;
-            println!("生成密码: {}", pwd);
-            // 输出强度信息
-            let estimate = zxcvbn(&pwd, &[]);
-            eprintln!("强度评估: {}", estimate.score());
+            if !valid {
+                return Err(anyhow::anyhow!("invalid code"));
+            }
+            println!("code is valid");
+        }
+
+        // eg: cargo run -- secret split -i fixtures/ed25519.sk -n 5 -t 3
+        Subcommand::Secret(SecretSubCommand::Split(opts)) => {
+            let shares =
+                process_secret_split(&opts.input, opts.shares, opts.threshold, opts.format)?;
+            match opts.output {
+                Some(dir) => {
+                    let paths = (1..=shares.len())
+                        .map(|i| dir.join(format!("share-{}.txt", i)))
+                        .collect::<Vec<_>>();
+                    // 先检查再写，避免只覆盖了一部分旧的 share
+                    if let Some(path) = paths.iter().find(|p| p.exists() && !opts.force) {
+                        return Err(anyhow::anyhow!(
+                            "{} already exists, use --force to overwrite",
+                            path.display()
+                        ));
+                    }
+                    // share 和原始 secret 一样敏感，只允许自己读写
+                    for (path, share) in paths.iter().zip(&shares) {
+                        write_private(path, share.as_bytes())?;
+                    }
+                    eprintln!("已写入 {} 份 share 到 {}", shares.len(), dir.display());
+                }
+                None => println!("{}", shares.join("\n")),
+            }
+        }
+
+        Subcommand::Secret(SecretSubCommand::Combine(opts)) => {
+            let secret = process_secret_combine(&opts.input)?;
+            // secret 可能是二进制的 key，原样写出
+            match opts.output {
+                Some(output) => write_private(output, &secret)?,
+                None => std::io::stdout().write_all(&secret)?,
+            }
+        }
+
+        // eg: cargo run -- vault init
+        // eg: cargo run -- vault gen github -l 24 -u me
+        Subcommand::Vault(opts) => {
+            let master_file = opts.master_file.as_deref();
+            let master = read_secret("Master password: ", master_file)?;
+            match opts.cmd {
+                VaultSubCommand::Init => {
+                    // 交互输入时确认一次，避免手误之后再也打不开
+                    if master_file.is_none() && std::io::stdin().is_terminal() {
+                        let confirm = read_secret("Confirm master password: ", None)?;
+                        if confirm != master {
+                            return Err(anyhow::anyhow!("master passwords do not match"));
+                        }
+                    }
+                    Vault::init(&opts.file, &master)?;
+                    eprintln!("已创建 vault: {}", opts.file);
+                }
+                VaultSubCommand::Add(add) => {
+                    let mut vault = Vault::open(&opts.file, &master)?;
+                    let prompt = format!("Password for {}: ", add.name);
+                    let password = read_secret(&prompt, add.password_file.as_deref())?;
+                    let entry = VaultEntry::new(password, add.username, add.url, add.notes);
+                    vault.add(&add.name, entry, add.force)?;
+                    vault.save()?;
+                }
+                // 只输出到 stdout，不碰剪贴板，方便 rcli vault get github | pbcopy 这样组合
+                VaultSubCommand::Get(get) => {
+                    let vault = Vault::open(&opts.file, &master)?;
+                    let entry = vault.get(&get.name)?;
+                    if get.json {
+                        println!("{}", serde_json::to_string_pretty(entry)?);
+                    } else {
+                        println!("{}", entry.password);
+                    }
+                }
+                VaultSubCommand::List => {
+                    let vault = Vault::open(&opts.file, &master)?;
+                    for (name, entry) in vault.list() {
+                        match &entry.username {
+                            Some(username) => println!("{}\t{}", name, username),
+                            None => println!("{}", name),
+                        }
+                    }
+                }
+                VaultSubCommand::Rm(rm) => {
+                    let mut vault = Vault::open(&opts.file, &master)?;
+                    vault.remove(&rm.name)?;
+                    vault.save()?;
+                }
+                VaultSubCommand::Gen(gen) => {
+                    let mut vault = Vault::open(&opts.file, &master)?;
+                    let config = GenPassConfig {
+                        upper: gen.classes.uppercase,
+                        lower: gen.classes.lowercase,
+                        number: gen.classes.number,
+                        symbol: gen.classes.symbol,
+                        ..GenPassConfig::new(gen.length)
+                    };
+                    let password = process_genpass(&config)?;
+                    let entry = VaultEntry::new(password.clone(), gen.username, gen.url, gen.notes);
+                    vault.add(&gen.name, entry, gen.force)?;
+                    vault.save()?;
+                    println!("{}", password);
+                }
+                VaultSubCommand::Export(export) => {
+                    let vault = Vault::open(&opts.file, &master)?;
+                    let json = vault.export_json()?;
+                    match export.output {
+                        Some(output) => write_private(output, json.as_bytes())?,
+                        None => println!("{}", json),
+                    }
+                }
+            }
+        }
+
+        // eg: cargo run -- pass check -i passwords.txt --user-inputs yxnne --min-score 3
+        // eg: echo 'password123' | cargo run -- pass check --json
+        Subcommand::Pass(PassSubCommand::Check(opts)) => {
+            let reports = process_pass_check(&opts.input, &opts.user_inputs)?;
+            if opts.json {
+                println!("{}", serde_json::to_string_pretty(&reports)?);
+            } else {
+                for report in &reports {
+                    print_strength_report(report);
+                }
+            }
+
+            if let Some(min) = opts.min_score {
+                let weak = reports.iter().filter(|r| r.score < min).count();
+                if weak > 0 {
+                    return Err(anyhow::anyhow!(
+                        "{} of {} passwords scored below {}",
+                        weak,
+                        reports.len(),
+                        min
+                    ));
+                }
+            }
         }
 
         // base64
         // cargo run -- base64 encode 自己输入 回车 后 ctrl + D 退出
         // cargo run -- base64 encode --format urlsafe -i Cargo.toml
+        // cargo run -- base64 decode -i fixtures/b64.txt --format urlsafe --hexdump
         Subcommand::Base64(opts) => match opts {
+            // rcli base64 encode/decode 等同于 rcli encode/decode --codec base64
             Base64SubCommand::Encode(opts) => {
                 // process_base64_encode(&opts.input)?;
                 // println!("encode {}", opts.input);
-                let encoded = process_encode(&opts.input, opts.format)?;
-                println!("{}", encoded);
+                encode_to_stdout(&opts.input, Codec::Base64(opts.format))?;
             }
             Base64SubCommand::Decode(opts) => {
                 // process_base64_decode(&opts.input)?;
                 // println!("decode {}", opts.input);
-                let decoded = process_decode(&opts.input, opts.format)?;
-                let decoded = String::from_utf8(decoded)?;
-                println!("{}", decoded);
+                decode_to_output(&opts.input, Codec::Base64(opts.format), &opts.output)?;
             }
         },
 
+        // cargo run -- encode --codec base58 -i Cargo.toml
+        // echo -n 'HelloWorld' | cargo run -- decode --codec z85
+        Subcommand::Encode(opts) => encode_to_stdout(&opts.input, opts.codec)?,
+        Subcommand::Decode(opts) => decode_to_output(&opts.input, opts.codec, &opts.output)?,
+
         // text 文本加密
         // eg: cargo run -- text sign -k fixtures/black3.txt
         // eg: cargo run -- text verify -k fixtures/black3.txt --signature p5s9akpKJuDYUH96WfJKbRekgIsRRveVPy0aEHu-14o
@@ -87,27 +513,168 @@ fn main() -> anyhow::Result<()> {
             TextSubCommand::Generate(opts) => {
                 // let key = process_text_generate_key(opts.format)?;
                 // println!("{}", key);
-                let key = process_text_generate_keye(opts.format)?;
+                let key = process_text_generate_keye(
+                    opts.format,
+                    opts.encoding,
+                    &mut seeded_rng(opts.seed),
+                )?;
                 // println!("{}", key);
 
                 match opts.format {
                     TextSignFormat::Black3 => {
                         let name = opts.output.join("black3.txt");
-                        fs::write(name, &key[0])?;
+                        // 和 text migrate 一样，私钥只允许自己读写
+                        write_private(name, &key[0])?;
                         // let key = Black3::new(key);
                         // println!("{}", key.key);
                     }
                     TextSignFormat::Ed25519 => {
                         let name = &opts.output;
-                        fs::write(name.join("ed25519.sk"), &key[0])?;
+                        write_private(name.join("ed25519.sk"), &key[0])?;
+                        // 公钥本来就是公开的，不需要限制权限
                         fs::write(name.join("ed25519.pk"), &key[1])?;
                         // let key = Ed25519Signer::new(key);
                         // println!("{}", key.key);
                     }
                 }
             }
+
+            // eg: cargo run -- text migrate -k fixtures/black3.txt -o black3.key
+            TextSubCommand::Migrate(opts) => {
+                let key = process_text_migrate_key(&opts.key, opts.encoding)?;
+                write_private(&opts.output, &key)?;
+                eprintln!("已迁移 key: {} -> {}", opts.key, opts.output);
+            }
         },
     }
 
     Ok(())
 }
+
+/// --seed 的输出可以被复现，每次使用都在 stderr 提醒
+fn seeded_rng(opts: SeedOpts) -> Box<dyn RngCore> {
+    if let Some(seed) = opts.seed {
+        eprintln!(
+            "警告: 使用了 --seed {}，输出可以被复现，不安全，只能用于测试和演示",
+            seed
+        );
+    }
+    build_rng(opts.seed)
+}
+
+/// 单个密码的输出: 默认打印密码，熵和强度评估输出到 stderr；--json 时输出结构化的结果
+fn print_password(label: &str, password: &str, entropy: f64, json: bool) -> anyhow::Result<()> {
+    if json {
+        let report = password_report(password, entropy);
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    println!("{}: {}", label, password);
+    eprintln!("熵: {:.1} bits ({})", entropy, entropy_label(entropy));
+    // 输出强度信息
+    let estimate = zxcvbn(password, &[]);
+    eprintln!("强度评估: {}", estimate.score());
+    Ok(())
+}
+
+/// 编码结果输出到 stdout，base64 是流式的，大文件也不会整个读进内存
+fn encode_to_stdout(input: &str, codec: Codec) -> anyhow::Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    process_codec_encode(input, &mut stdout, codec)?;
+    writeln!(stdout)?;
+    Ok(())
+}
+
+/// 写文件或者输出到管道时直接流式解码，终端和 --hexdump 预览才需要先拿到完整结果
+/// 文件和管道拿到的是原始字节，末尾不加换行（和 base64 -d 一样，方便 | 给其他命令）；
+/// 只有文本输出到终端时才像以前的 println! 一样补一个换行
+fn decode_to_output(input: &str, codec: Codec, opts: &DecodeOutputOpts) -> anyhow::Result<()> {
+    match opts.output.as_deref() {
+        Some(path) if path != "-" => {
+            let n = decode_to_file(input, codec, Path::new(path))?;
+            eprintln!("已写入 {} 字节到 {}", n, path);
+        }
+        _ if !opts.hexdump && !std::io::stdout().is_terminal() => {
+            let mut stdout = std::io::stdout().lock();
+            process_codec_decode(input, &mut stdout, codec)?;
+        }
+        _ => {
+            let mut decoded = Vec::new();
+            process_codec_decode(input, &mut decoded, codec)?;
+            write_decoded(&decoded, opts)?;
+        }
+    }
+    Ok(())
+}
+
+/// 先解码到同目录的临时文件，成功后再 rename，解码到一半出错不会留下截断的输出文件
+fn decode_to_file(input: &str, codec: Codec, path: &Path) -> anyhow::Result<u64> {
+    let tmp = PathBuf::from(format!("{}.tmp", path.display()));
+    let write = || -> anyhow::Result<u64> {
+        let mut file = std::io::BufWriter::new(fs::File::create(&tmp)?);
+        let n = process_codec_decode(input, &mut file, codec)?;
+        file.into_inner()?.sync_all()?;
+        Ok(n)
+    };
+    match write() {
+        Ok(n) => {
+            fs::rename(&tmp, path)?;
+            Ok(n)
+        }
+        Err(e) => {
+            let _ = fs::remove_file(&tmp);
+            Err(e)
+        }
+    }
+}
+
+/// 输出到终端: --hexdump 只做预览，文本照旧打印
+/// 二进制数据默认不往终端里写，避免把终端弄乱，只有 --force 时才原样输出（-o - 也不行）
+fn write_decoded(data: &[u8], opts: &DecodeOutputOpts) -> anyhow::Result<()> {
+    if opts.hexdump {
+        print!("{}", hexdump(data));
+        return Ok(());
+    }
+    let is_text = std::str::from_utf8(data).is_ok();
+    if is_text && opts.output.is_none() {
+        println!("{}", String::from_utf8_lossy(data));
+        return Ok(());
+    }
+    let mut stdout = std::io::stdout();
+    if !is_text && !opts.force && stdout.is_terminal() {
+        return Err(anyhow::anyhow!(
+            "decoded data is binary ({} bytes), use -o <file>, --hexdump or --force",
+            data.len()
+        ));
+    }
+    stdout.write_all(data)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+fn print_strength_report(report: &StrengthReport) {
+    println!(
+        "line {}: score {}/4, guesses 10^{:.1}",
+        report.line, report.score, report.guesses_log10
+    );
+    let times = &report.crack_times;
+    println!(
+        "  crack time: online throttled {}, online {}, offline slow {}, offline fast {}",
+        times.online_throttling,
+        times.online_no_throttling,
+        times.offline_slow_hashing,
+        times.offline_fast_hashing
+    );
+    let patterns = report
+        .patterns
+        .iter()
+        .map(|p| format!("{} ({} chars)", p.pattern, p.length))
+        .collect::<Vec<_>>();
+    println!("  patterns: {}", patterns.join(", "));
+    if let Some(warning) = &report.warning {
+        println!("  warning: {}", warning);
+    }
+    for suggestion in &report.suggestions {
+        println!("  suggestion: {}", suggestion);
+    }
+}
@@ -0,0 +1,120 @@
+use std::io::Read;
+
+use anyhow::{anyhow, Result};
+use rand::{seq::IndexedRandom, Rng};
+
+use crate::get_reader;
+
+const BASE62: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+// 62^6 > 2^32，6 个 base62 字符刚好放下 CRC32
+const CHECKSUM_LEN: usize = 6;
+
+/// 生成形如 rk_live_<随机 base62><CRC32 校验> 的 API key，类似 GitHub / Stripe 的 token
+pub fn process_apikey(prefix: &str, length: usize) -> Result<String> {
+    generate_apikey(prefix, length, &mut rand::rng())
+}
+
+pub fn generate_apikey<R: Rng + ?Sized>(
+    prefix: &str,
+    length: usize,
+    rng: &mut R,
+) -> Result<String> {
+    verify_prefix(prefix)?;
+    if length == 0 {
+        return Err(anyhow!("key length must be greater than 0"));
+    }
+    let body = (0..length)
+        .map(|_| *BASE62.choose(rng).expect("alphabet won't be empty") as char)
+        .collect::<String>();
+    let key = format!("{}_{}", prefix, body);
+    let checksum = checksum(&key);
+    Ok(key + &checksum)
+}
+
+/// 离线校验 key 的格式和校验和，不需要访问服务端
+pub fn verify_apikey(key: &str) -> Result<()> {
+    // 前缀里可以有 _，随机部分是 base62 不会有 _，所以按最后一个 _ 切分
+    let (prefix, rest) = key
+        .rsplit_once('_')
+        .ok_or_else(|| anyhow!("missing prefix separator '_'"))?;
+    verify_prefix(prefix)?;
+    if rest.len() <= CHECKSUM_LEN {
+        return Err(anyhow!("key is too short"));
+    }
+    if !rest.bytes().all(|b| BASE62.contains(&b)) {
+        return Err(anyhow!("key body is not base62"));
+    }
+    let (body, expected) = rest.split_at(rest.len() - CHECKSUM_LEN);
+    if checksum(&format!("{}_{}", prefix, body)) != expected {
+        return Err(anyhow!("checksum mismatch"));
+    }
+    Ok(())
+}
+
+/// 逐行校验，返回每一行的错误信息，None 表示通过
+pub fn process_apikey_verify(input: &str) -> Result<Vec<Option<String>>> {
+    let mut reader = get_reader(input)?;
+    let mut buf = String::new();
+    reader.read_to_string(&mut buf)?;
+    Ok(buf
+        .lines()
+        .map(|key| verify_apikey(key.trim()).err().map(|e| e.to_string()))
+        .collect())
+}
+
+fn verify_prefix(prefix: &str) -> Result<()> {
+    if prefix.is_empty()
+        || !prefix
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_')
+    {
+        return Err(anyhow!(
+            "invalid prefix {:?}, only letters, digits and '_' are allowed",
+            prefix
+        ));
+    }
+    Ok(())
+}
+
+/// 对 prefix_body 计算 CRC32，编码成定长 6 位的 base62，改动前缀也能被发现
+fn checksum(data: &str) -> String {
+    let mut value = crc32fast::hash(data.as_bytes()) as u64;
+    let mut ret = [b'0'; CHECKSUM_LEN];
+    for c in ret.iter_mut().rev() {
+        *c = BASE62[(value % 62) as usize];
+        value /= 62;
+    }
+    String::from_utf8_lossy(&ret).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apikey_verify() -> Result<()> {
+        let key = process_apikey("rk_live", 30)?;
+        assert!(key.starts_with("rk_live_"));
+        assert_eq!(key.len(), "rk_live_".len() + 30 + CHECKSUM_LEN);
+        verify_apikey(&key)?;
+
+        // 改掉随机部分或前缀都会导致校验失败
+        let mut tampered = key.clone().into_bytes();
+        let i = "rk_live_".len();
+        tampered[i] = if tampered[i] == b'a' { b'b' } else { b'a' };
+        assert!(verify_apikey(&String::from_utf8(tampered)?).is_err());
+        assert!(verify_apikey(&key.replacen("rk_live", "rk_test", 1)).is_err());
+
+        assert!(verify_apikey("rk_short").is_err());
+        assert!(verify_apikey("nounderscore").is_err());
+        assert!(process_apikey("rk-live", 30).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_checksum() {
+        assert_eq!(checksum(""), "000000");
+        // crc32("123456789") = 0xCBF43926
+        assert_eq!(checksum("123456789"), "3jZRME");
+    }
+}
@@ -1,44 +1,118 @@
+use std::io::{ErrorKind, Read, Write};
+
 use base64::{
-    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+    engine::{
+        general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+        GeneralPurpose,
+    },
     Engine as _,
 };
 
 use crate::{get_reader, Base64Format};
 
+// 每次处理的块大小: 编码按 3 字节对齐，中间的块编码后不会出现填充；解码按 4 个字符对齐
+const ENCODE_CHUNK: usize = 3 * 16 * 1024;
+const DECODE_CHUNK: usize = 4 * 16 * 1024;
+
 pub fn process_encode(input: &str, format: Base64Format) -> anyhow::Result<String> {
     // `if` and `else` have incompatible types expected `Stdin`, found `File`
     // Box<dyn std::io::Read> 封装到box里面是一种常见的模式，用于处理不同类型
     let mut reader = get_reader(input)?;
-
     let mut buf = Vec::new();
-    reader.read_to_end(&mut buf)?;
-    // let encoded = URL_SAFE.encode(buf);
-
-    let encoded = match format {
-        Base64Format::UrlSafe => URL_SAFE_NO_PAD.encode(buf),
-        Base64Format::Standard => STANDARD.encode(buf),
-    };
-    // println!("{}", encoded);
-
-    Ok(encoded)
+    encode_stream(&mut reader, &mut buf, format)?;
+    Ok(String::from_utf8(buf)?)
 }
 
 pub fn process_decode(input: &str, format: Base64Format) -> anyhow::Result<Vec<u8>> {
     let mut reader = get_reader(input)?;
-    let mut buf = String::new();
-    reader.read_to_string(&mut buf)?;
+    let mut buf = Vec::new();
+    decode_stream(&mut reader, &mut buf, format)?;
+    Ok(buf)
+}
 
-    let buf = buf.trim();
+/// 按固定大小的块编码，内存占用和输入大小无关
+pub fn encode_stream(
+    reader: &mut dyn Read,
+    writer: &mut dyn Write,
+    format: Base64Format,
+) -> anyhow::Result<u64> {
+    let engine = engine(format);
+    let mut buf = vec![0u8; ENCODE_CHUNK];
+    let mut out = String::with_capacity(ENCODE_CHUNK / 3 * 4);
+    let mut written = 0;
+    loop {
+        // 只有最后一块会不满，保证填充只出现在结尾
+        let n = read_full(reader, &mut buf)?;
+        out.clear();
+        engine.encode_string(&buf[..n], &mut out);
+        writer.write_all(out.as_bytes())?;
+        written += out.len() as u64;
+        if n < buf.len() {
+            break;
+        }
+    }
+    writer.flush()?;
+    Ok(written)
+}
 
-    let decoded = match format {
-        Base64Format::UrlSafe => URL_SAFE_NO_PAD.decode(buf)?,
-        Base64Format::Standard => STANDARD.decode(buf)?,
-    };
+/// 按块解码，跳过空白和换行（比如 base64 -w 76 的输出），不满 4 个字符的部分留到下一块
+pub fn decode_stream(
+    reader: &mut dyn Read,
+    writer: &mut dyn Write,
+    format: Base64Format,
+) -> anyhow::Result<u64> {
+    let engine = engine(format);
+    let mut buf = vec![0u8; DECODE_CHUNK];
+    let mut pending = Vec::with_capacity(DECODE_CHUNK + 4);
+    let mut out = Vec::with_capacity(DECODE_CHUNK / 4 * 3 + 3);
+    let mut written = 0;
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(n) => n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        if n == 0 {
+            break;
+        }
+        pending.extend(buf[..n].iter().filter(|b| !b.is_ascii_whitespace()));
+        let end = pending.len() / 4 * 4;
+        out.clear();
+        engine.decode_vec(&pending[..end], &mut out)?;
+        writer.write_all(&out)?;
+        written += out.len() as u64;
+        pending.drain(..end);
+    }
+    // urlsafe 没有填充，结尾可能剩下 2 到 3 个字符
+    if !pending.is_empty() {
+        out.clear();
+        engine.decode_vec(&pending, &mut out)?;
+        writer.write_all(&out)?;
+        written += out.len() as u64;
+    }
+    writer.flush()?;
+    Ok(written)
+}
 
-    // let decoded = String::from_utf8(decoded)?;
-    // println!("{}", decoded);
+fn engine(format: Base64Format) -> &'static GeneralPurpose {
+    match format {
+        Base64Format::UrlSafe => &URL_SAFE_NO_PAD,
+        Base64Format::Standard => &STANDARD,
+    }
+}
 
-    Ok(decoded)
+// 一直读到 buf 填满或者 EOF，read 可能只返回一部分数据（比如 stdin 是管道时）
+fn read_full(reader: &mut dyn Read, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
 }
 
 #[cfg(test)]
@@ -58,4 +132,31 @@ mod tests {
         let format = Base64Format::UrlSafe;
         process_decode(input, format).unwrap();
     }
+
+    #[test]
+    fn test_stream_roundtrip() -> anyhow::Result<()> {
+        // 比一个块大，长度不是 3 的倍数，覆盖跨块和填充的情况
+        let data = (0..ENCODE_CHUNK * 3 + 2)
+            .map(|i| (i * 31 % 251) as u8)
+            .collect::<Vec<_>>();
+        for format in [Base64Format::Standard, Base64Format::UrlSafe] {
+            let mut encoded = Vec::new();
+            encode_stream(&mut data.as_slice(), &mut encoded, format)?;
+            assert_eq!(encoded, engine(format).encode(&data).into_bytes());
+
+            // 模拟 base64 -w 76 的换行输出
+            let wrapped = encoded
+                .chunks(76)
+                .flat_map(|line| line.iter().copied().chain(*b"\r\n"))
+                .collect::<Vec<_>>();
+            let mut decoded = Vec::new();
+            let n = decode_stream(&mut wrapped.as_slice(), &mut decoded, format)?;
+            assert_eq!(n, data.len() as u64);
+            assert_eq!(decoded, data);
+        }
+
+        let mut out = Vec::new();
+        assert!(decode_stream(&mut &b"aGk*"[..], &mut out, Base64Format::Standard).is_err());
+        Ok(())
+    }
 }
@@ -0,0 +1,265 @@
+use std::{io::Write, sync::LazyLock};
+
+use anyhow::{anyhow, Result};
+use data_encoding::{
+    Encoding, Specification, BASE32, BASE32HEX, BASE32HEX_NOPAD, BASE32_NOPAD, HEXLOWER,
+    HEXLOWER_PERMISSIVE, HEXUPPER,
+};
+
+use crate::{decode_stream, encode_stream, get_reader, Codec};
+
+// Crockford base32: 去掉了 I L O U，解码时大小写都可以，I L 当作 1，O 当作 0，忽略分隔用的 -
+static CROCKFORD: LazyLock<Encoding> = LazyLock::new(|| {
+    let mut spec = Specification::new();
+    spec.symbols.push_str("0123456789ABCDEFGHJKMNPQRSTVWXYZ");
+    spec.translate.from.push_str("abcdefghjkmnpqrstvwxyzIiLlOo");
+    spec.translate.to.push_str("ABCDEFGHJKMNPQRSTVWXYZ111100");
+    spec.ignore.push('-');
+    spec.encoding().expect("crockford spec is valid")
+});
+
+const ASCII85: &[u8] =
+    b"!\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstu";
+const Z85: &[u8] =
+    b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ.-:+=^!/*?&<>()[]{}@%$#";
+
+/// 编码 input 写到 writer，返回写出的字节数
+/// base64 走 b64 的流式实现，其他编码需要先读完整个输入
+pub fn process_codec_encode(input: &str, writer: &mut dyn Write, codec: Codec) -> Result<u64> {
+    let mut reader = get_reader(input)?;
+    if let Codec::Base64(format) = codec {
+        return encode_stream(&mut reader, writer, format);
+    }
+    let mut buf = Vec::new();
+    reader.read_to_end(&mut buf)?;
+    let encoded = encode_bytes(&buf, codec)?;
+    writer.write_all(encoded.as_bytes())?;
+    Ok(encoded.len() as u64)
+}
+
+/// 解码 input 写到 writer，返回写出的字节数
+pub fn process_codec_decode(input: &str, writer: &mut dyn Write, codec: Codec) -> Result<u64> {
+    let mut reader = get_reader(input)?;
+    if let Codec::Base64(format) = codec {
+        return decode_stream(&mut reader, writer, format);
+    }
+    let mut buf = String::new();
+    reader.read_to_string(&mut buf)?;
+    let decoded = decode_str(&buf, codec)?;
+    writer.write_all(&decoded)?;
+    Ok(decoded.len() as u64)
+}
+
+pub fn encode_bytes(data: &[u8], codec: Codec) -> Result<String> {
+    let encoded = match codec {
+        Codec::Base64(format) => {
+            let mut buf = Vec::new();
+            encode_stream(&mut &data[..], &mut buf, format)?;
+            String::from_utf8(buf)?
+        }
+        Codec::Hex => HEXLOWER.encode(data),
+        Codec::HexUpper => HEXUPPER.encode(data),
+        Codec::Base32 => BASE32.encode(data),
+        Codec::Base32Hex => BASE32HEX.encode(data),
+        Codec::Crockford => CROCKFORD.encode(data),
+        Codec::Base58 => bs58::encode(data).into_string(),
+        Codec::Base58Flickr => bs58::encode(data)
+            .with_alphabet(bs58::Alphabet::FLICKR)
+            .into_string(),
+        Codec::Base58Check => bs58::encode(data).with_check().into_string(),
+        Codec::Ascii85 => format!("<~{}~>", base85_encode(data, ASCII85, true)),
+        Codec::Z85 => {
+            // Z85 规范要求长度是 4 的倍数
+            if !data.len().is_multiple_of(4) {
+                return Err(anyhow!("z85 input length must be a multiple of 4"));
+            }
+            base85_encode(data, Z85, false)
+        }
+    };
+    Ok(encoded)
+}
+
+pub fn decode_str(data: &str, codec: Codec) -> Result<Vec<u8>> {
+    // 和 rcli base64 decode 一样，允许换行和空白，xxd -p、base32 -w 这类按行折断的输出也能解码
+    let data = data.split_ascii_whitespace().collect::<String>();
+    let data = data.as_str();
+    let decoded = match codec {
+        Codec::Base64(format) => {
+            let mut buf = Vec::new();
+            decode_stream(&mut data.as_bytes(), &mut buf, format)?;
+            buf
+        }
+        Codec::Hex | Codec::HexUpper => HEXLOWER_PERMISSIVE.decode(data.as_bytes())?,
+        // 有没有 = 填充都可以解码
+        Codec::Base32 if data.ends_with('=') => BASE32.decode(data.as_bytes())?,
+        Codec::Base32 => BASE32_NOPAD.decode(data.as_bytes())?,
+        Codec::Base32Hex if data.ends_with('=') => BASE32HEX.decode(data.as_bytes())?,
+        Codec::Base32Hex => BASE32HEX_NOPAD.decode(data.as_bytes())?,
+        Codec::Crockford => CROCKFORD.decode(data.as_bytes())?,
+        Codec::Base58 => bs58::decode(data).into_vec()?,
+        Codec::Base58Flickr => bs58::decode(data)
+            .with_alphabet(bs58::Alphabet::FLICKR)
+            .into_vec()?,
+        // 校验失败会报错，返回的数据不包含 4 字节校验和
+        Codec::Base58Check => bs58::decode(data).with_check(None).into_vec()?,
+        Codec::Ascii85 => {
+            let data = data.strip_prefix("<~").unwrap_or(data);
+            let data = data.strip_suffix("~>").unwrap_or(data);
+            base85_decode(data, ASCII85, true)?
+        }
+        Codec::Z85 => {
+            if !data.len().is_multiple_of(5) {
+                return Err(anyhow!("z85 input length must be a multiple of 5"));
+            }
+            base85_decode(data, Z85, false)?
+        }
+    };
+    Ok(decoded)
+}
+
+/// 每 4 个字节当作一个大端 u32，写成 5 个 85 进制数字
+/// 最后不满 4 字节时补 0，只输出 n + 1 个字符；ascii85 里全 0 的整组缩写成 z
+fn base85_encode(data: &[u8], alphabet: &[u8], zero_shortcut: bool) -> String {
+    let mut ret = String::with_capacity(data.len() * 5 / 4 + 5);
+    for chunk in data.chunks(4) {
+        let mut group = [0u8; 4];
+        group[..chunk.len()].copy_from_slice(chunk);
+        let mut value = u32::from_be_bytes(group);
+        if zero_shortcut && chunk.len() == 4 && value == 0 {
+            ret.push('z');
+            continue;
+        }
+        let mut digits = [0u8; 5];
+        for d in digits.iter_mut().rev() {
+            *d = alphabet[(value % 85) as usize];
+            value /= 85;
+        }
+        ret.extend(digits[..chunk.len() + 1].iter().map(|d| *d as char));
+    }
+    ret
+}
+
+/// base85_encode 的逆过程，最后不满 5 个字符时用最大的数字补齐，输出 n - 1 个字节，空白字符会被忽略
+fn base85_decode(data: &str, alphabet: &[u8], zero_shortcut: bool) -> Result<Vec<u8>> {
+    let mut ret = Vec::with_capacity(data.len() * 4 / 5 + 4);
+    let mut group = Vec::with_capacity(5);
+    for c in data.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        if zero_shortcut && c == b'z' {
+            if !group.is_empty() {
+                return Err(anyhow!("invalid base85 input, 'z' inside a group"));
+            }
+            ret.extend_from_slice(&[0; 4]);
+            continue;
+        }
+        let digit = alphabet
+            .iter()
+            .position(|a| *a == c)
+            .ok_or_else(|| anyhow!("invalid base85 character {:?}", c as char))?;
+        group.push(digit as u8);
+        if group.len() == 5 {
+            flush_base85_group(&mut group, &mut ret)?;
+        }
+    }
+    if !group.is_empty() {
+        flush_base85_group(&mut group, &mut ret)?;
+    }
+    Ok(ret)
+}
+
+fn flush_base85_group(group: &mut Vec<u8>, ret: &mut Vec<u8>) -> Result<()> {
+    let len = group.len();
+    if len == 1 {
+        return Err(anyhow!("invalid base85 input, dangling character"));
+    }
+    group.resize(5, 84);
+    let value = group
+        .iter()
+        .try_fold(0u32, |acc, d| acc.checked_mul(85)?.checked_add(*d as u32))
+        .ok_or_else(|| anyhow!("invalid base85 group, value overflows"))?;
+    ret.extend_from_slice(&value.to_be_bytes()[..len - 1]);
+    group.clear();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Base64Format;
+
+    #[test]
+    fn test_codec_vectors() -> Result<()> {
+        let cases: &[(Codec, &[u8], &str)] = &[
+            (Codec::Hex, b"\xab\x01", "ab01"),
+            (Codec::HexUpper, b"\xab\x01", "AB01"),
+            // RFC 4648 第 10 节的测试向量
+            (Codec::Base32, b"foobar", "MZXW6YTBOI======"),
+            (Codec::Base32Hex, b"foobar", "CPNMUOJ1E8======"),
+            (Codec::Crockford, b"foobar", "CSQPYRK1E8"),
+            (Codec::Base58, b"Hello World!", "2NEpo7TZRRrLZSi2U"),
+            (Codec::Ascii85, b"sure.", "<~F*2M7/c~>"),
+            (Codec::Ascii85, b"\0\0\0\0Man ", "<~z9jqo^~>"),
+            // ZeroMQ RFC 32 的例子
+            (
+                Codec::Z85,
+                b"\x86\x4F\xD2\x6F\xB5\x59\xF7\x5B",
+                "HelloWorld",
+            ),
+        ];
+        for (codec, data, text) in cases {
+            assert_eq!(encode_bytes(data, *codec)?, *text, "{}", codec);
+            assert_eq!(decode_str(text, *codec)?, *data, "{}", codec);
+        }
+
+        // 所有编码都和 rcli base64 decode 一样接受按行折断的输入
+        let wrapped: &[(Codec, &str)] = &[
+            (Codec::Base64(Base64Format::Standard), "Zm9v\nYmFy\n"),
+            (Codec::Hex, "666f\n6f62\n6172\n"),
+            (Codec::Base32, "MZXW6\nYTBOI\n======\n"),
+            (Codec::Crockford, "CSQPY\nRK1E8\n"),
+            (Codec::Base58, "t1Zv\r\n2yaZ\n"),
+            (Codec::Z85, "Hello\nWorld\n"),
+        ];
+        for (codec, text) in wrapped {
+            let expected: &[u8] = if *codec == Codec::Z85 {
+                b"\x86\x4F\xD2\x6F\xB5\x59\xF7\x5B"
+            } else {
+                b"foobar"
+            };
+            assert_eq!(decode_str(text, *codec)?, expected, "{}", codec);
+        }
+        assert_eq!(decode_str("csqpyrk1e8", Codec::Crockford)?, b"foobar");
+        assert_eq!(decode_str("MZXW6YTBOI", Codec::Base32)?, b"foobar");
+        assert!(encode_bytes(b"abc", Codec::Z85).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_codec_roundtrip() -> Result<()> {
+        let data = (0..=255u8).collect::<Vec<_>>();
+        for codec in [
+            Codec::Base64(Base64Format::Standard),
+            Codec::Base64(Base64Format::UrlSafe),
+            Codec::Hex,
+            Codec::Base32,
+            Codec::Crockford,
+            Codec::Base58,
+            Codec::Base58Flickr,
+            Codec::Base58Check,
+            Codec::Ascii85,
+            Codec::Z85,
+        ] {
+            assert_eq!(
+                decode_str(&encode_bytes(&data, codec)?, codec)?,
+                data,
+                "{}",
+                codec
+            );
+        }
+
+        // base58check 改掉一个字符就会校验失败
+        let text = encode_bytes(b"hello", Codec::Base58Check)?;
+        let tampered = text.replacen(&text[..1], if &text[..1] == "2" { "3" } else { "2" }, 1);
+        assert!(decode_str(&tampered, Codec::Base58Check).is_err());
+        Ok(())
+    }
+}
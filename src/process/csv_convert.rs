@@ -1,35 +1,391 @@
-use anyhow::Result;
-use csv::Reader;
-// use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, SecondsFormat, Utc};
+use csv::{Reader, StringRecord};
+use regex::Regex;
+use serde::Deserialize;
 use serde_json::Value;
-use std::fs;
 
-use crate::cli::OutputFormat;
-pub fn process_csv(input: &str, output: String, format: OutputFormat) -> Result<()> {
-    let mut reader = Reader::from_path(input)?;
-    // ? 相当于做了match
-    // match reader {
-    //     Ok(v) => ...
-    //     Err(e) => return Err(e.into()),
-    // }
+use crate::{
+    cli::{DateColumn, DateOutput, OutputFormat},
+    par_map,
+};
+
+// 去掉日期后面的附加说明，比如 "Apr 18, 1990 (29)" 里的 " (29)"
+// LazyLock: 第一次使用时才编译正则，之后复用
+static ANNOTATION_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\s*[(\[][^)\]]*[)\]]\s*$").expect("valid regex"));
 
+/// 把多个输入合并转换到一个输出文件
+pub fn process_csv(
+    inputs: &[String],
+    output: String,
+    format: OutputFormat,
+    yaml_stream: bool,
+    dates: &[DateColumn],
+    date_output: DateOutput,
+    jobs: usize,
+) -> Result<()> {
     let mut ret = Vec::with_capacity(128);
-    let headers = reader.headers()?.clone();
-    for result in reader.records() {
-        // headers.iter() -> 使用 headers 的迭代器
-        // record.iter() -> 使用 record 的迭代器
-        // zip() -> 将两个迭代器合并为一个元组的迭代器 [(header, record), ..]
-        // collect::<Value>() -> 将元组的迭代器转换为 JSON Value
-        let record = result?;
-        let json_value = headers.iter().zip(record.iter()).collect::<Value>();
-        ret.push(json_value);
+    for input in inputs {
+        ret.extend(read_rows(input, dates, date_output, jobs)?);
+    }
+    write_rows(&ret, output, format, yaml_stream)
+}
+
+/// 每个输入单独转换，输出到 out_dir/<文件名>.{format}
+#[allow(clippy::too_many_arguments)]
+pub fn process_csv_each(
+    inputs: &[String],
+    out_dir: &Path,
+    format: OutputFormat,
+    yaml_stream: bool,
+    dates: &[DateColumn],
+    date_output: DateOutput,
+    jobs: usize,
+    force: bool,
+) -> Result<()> {
+    let outputs = plan_outputs(inputs, out_dir, format, force)?;
+    for (input, output) in inputs.iter().zip(outputs) {
+        let rows = read_rows(input, dates, date_output, jobs)?;
+        write_rows(&rows, output, format, yaml_stream)?;
+    }
+    Ok(())
+}
+
+// 写之前先算出所有输出路径，有冲突时一个文件都不写
+// 已存在的输出文件只有加了 force 才会覆盖，输入文件任何时候都不会被覆盖
+fn plan_outputs(
+    inputs: &[String],
+    out_dir: &Path,
+    format: OutputFormat,
+    force: bool,
+) -> Result<Vec<PathBuf>> {
+    // 规范化之后再比较，./data.yaml、data.yaml 和 ../dir/data.yaml 是同一个文件
+    let canonical_dir = fs::canonicalize(out_dir)?;
+    let mut outputs = Vec::with_capacity(inputs.len());
+    let mut seen = HashMap::new();
+    for input in inputs {
+        let stem = Path::new(input)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| anyhow!("invalid input file name: {}", input))?;
+        // 不用 with_extension，players.2020.csv 应该输出 players.2020.json 而不是 players.json
+        let file_name = format!("{}.{}", stem, format);
+        let output = out_dir.join(&file_name);
+        // 比如 -i data.yaml --format yaml --out-dir . 会覆盖输入文件
+        if canonical_dir.join(&file_name) == fs::canonicalize(input)? {
+            return Err(anyhow!("output {} would overwrite its input", input));
+        }
+        if output.exists() && !force {
+            return Err(anyhow!(
+                "output {} already exists, use --force to overwrite",
+                output.display()
+            ));
+        }
+        // a/x.csv 和 b/x.csv 都会输出到 out_dir/x.json
+        if let Some(other) = seen.insert(output.clone(), input) {
+            return Err(anyhow!(
+                "{} and {} would both write to {}",
+                other,
+                input,
+                output.display()
+            ));
+        }
+        outputs.push(output);
     }
+    Ok(outputs)
+}
+
+// 按扩展名选择读取方式，除了 csv 之外也接受 yaml / json / jsonl
+fn read_rows(
+    input: &str,
+    dates: &[DateColumn],
+    date_output: DateOutput,
+    jobs: usize,
+) -> Result<Vec<Value>> {
+    let ext = Path::new(input)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    let rows = match ext.as_str() {
+        "yaml" | "yml" => {
+            let records = read_yaml_stream(&fs::read_to_string(input)?)?;
+            if let Some(first) = records.first() {
+                check_date_columns(input, record_columns(first), dates)?;
+            }
+            par_map(jobs, &records, |i, record| {
+                normalize_row(record.clone(), i + 1, dates, date_output)
+            })?
+        }
+        "json" | "jsonl" | "ndjson" => {
+            let records = read_json_stream(&fs::read_to_string(input)?)?;
+            if let Some(first) = records.first() {
+                check_date_columns(input, record_columns(first), dates)?;
+            }
+            par_map(jobs, &records, |i, record| {
+                normalize_row(record.clone(), i + 1, dates, date_output)
+            })?
+        }
+        _ => {
+            let mut reader = Reader::from_path(input)?;
+            // ? 相当于做了match
+            // match reader {
+            //     Ok(v) => ...
+            //     Err(e) => return Err(e.into()),
+            // }
+
+            // csv 的读取只能顺序进行，先把记录读出来，再把转换分给多个线程
+            let headers = reader.headers()?.clone();
+            check_date_columns(input, headers.iter(), dates)?;
+            let records = reader.records().collect::<Result<Vec<_>, _>>()?;
+            par_map(jobs, &records, |i, record| {
+                // 行号 +2：跳过表头，且从 1 开始计数
+                normalize_row(to_row(&headers, record), i + 2, dates, date_output)
+            })?
+        }
+    };
+
+    let mut ret = Vec::with_capacity(rows.len());
+    for (row, errors) in rows {
+        errors.iter().for_each(|e| eprintln!("{}: {}", input, e));
+        ret.push(row);
+    }
+    Ok(ret)
+}
 
+// --parse-date 指定的列必须存在，否则拼错列名时所有行都会被悄悄跳过
+fn check_date_columns<'a>(
+    input: &str,
+    columns: impl IntoIterator<Item = &'a str>,
+    dates: &[DateColumn],
+) -> Result<()> {
+    let columns = columns.into_iter().collect::<HashSet<_>>();
+    match dates.iter().find(|d| !columns.contains(d.column.as_str())) {
+        Some(date) => Err(anyhow!(
+            "{}: --parse-date column {:?} not found",
+            input,
+            date.column
+        )),
+        None => Ok(()),
+    }
+}
+
+// yaml / jsonl 没有表头，用第一条记录的字段当作列名
+fn record_columns(record: &Value) -> Vec<&str> {
+    match record {
+        Value::Object(map) => map.keys().map(|k| k.as_str()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// 读取多文档 yaml（--- 分隔），每个文档可以是一行（对象）或者多行（数组）
+pub fn read_yaml_stream(content: &str) -> Result<Vec<Value>> {
+    let mut rows = Vec::new();
+    for doc in serde_yaml::Deserializer::from_str(content) {
+        push_records(&mut rows, Value::deserialize(doc)?);
+    }
+    Ok(rows)
+}
+
+/// 读取 JSON Lines，也兼容普通的 json 数组和连续拼接的多个 json
+pub fn read_json_stream(content: &str) -> Result<Vec<Value>> {
+    let mut rows = Vec::new();
+    for value in serde_json::Deserializer::from_str(content).into_iter::<Value>() {
+        push_records(&mut rows, value?);
+    }
+    Ok(rows)
+}
+
+fn push_records(rows: &mut Vec<Value>, value: Value) {
+    match value {
+        Value::Array(values) => rows.extend(values),
+        // 空文档（比如结尾多了一个 ---）直接跳过
+        Value::Null => {}
+        value => rows.push(value),
+    }
+}
+
+fn write_rows(
+    rows: &[Value],
+    output: impl AsRef<Path>,
+    format: OutputFormat,
+    yaml_stream: bool,
+) -> Result<()> {
     let content = match format {
-        OutputFormat::Json => serde_json::to_string_pretty(&ret)?,
-        OutputFormat::Yaml => serde_yaml::to_string(&ret)?,
+        OutputFormat::Json => serde_json::to_string_pretty(rows)?,
+        OutputFormat::Jsonl => {
+            let mut content = String::new();
+            for row in rows {
+                content.push_str(&serde_json::to_string(row)?);
+                content.push('\n');
+            }
+            content
+        }
+        // 每一行输出一个 --- 文档，kubectl 之类的工具可以直接消费
+        OutputFormat::Yaml if yaml_stream => {
+            let mut content = String::new();
+            for row in rows {
+                content.push_str("---\n");
+                content.push_str(&serde_yaml::to_string(row)?);
+            }
+            content
+        }
+        OutputFormat::Yaml => serde_yaml::to_string(rows)?,
     };
     fs::write(output, content)?;
-
     Ok(())
 }
+
+// 把一条 csv 记录转成 JSON 对象
+fn to_row(headers: &StringRecord, record: &StringRecord) -> Value {
+    // headers.iter() -> 使用 headers 的迭代器
+    // record.iter() -> 使用 record 的迭代器
+    // zip() -> 将两个迭代器合并为一个元组的迭代器 [(header, record), ..]
+    // collect::<Value>() -> 将元组的迭代器转换为 JSON Value
+    headers.iter().zip(record.iter()).collect::<Value>()
+}
+
+// 对一行做日期解析，同时返回解析失败的信息
+fn normalize_row(
+    mut row: Value,
+    line: usize,
+    dates: &[DateColumn],
+    date_output: DateOutput,
+) -> (Value, Vec<String>) {
+    let mut errors = Vec::new();
+    let Value::Object(map) = &mut row else {
+        return (row, errors);
+    };
+    for date in dates {
+        let Some(Value::String(raw)) = map.get(&date.column) else {
+            continue;
+        };
+        match normalize_date(raw, &date.format, date_output) {
+            Ok(v) => {
+                map.insert(date.column.clone(), v);
+            }
+            // 解析失败保留原值，只把出错的行报告出来
+            Err(e) => errors.push(format!(
+                "row {}: column {:?} value {:?}: {}",
+                line, date.column, raw, e
+            )),
+        }
+    }
+    (row, errors)
+}
+
+/// 按 strftime 格式解析日期，输出 rfc3339 字符串或 unix 时间戳
+pub fn normalize_date(value: &str, format: &str, output: DateOutput) -> Result<Value> {
+    let value = ANNOTATION_RE.replace(value.trim(), "");
+
+    // 先尝试带时区的格式，再尝试不带时区的日期时间，最后只有日期（按 00:00:00 UTC 处理）
+    let datetime = if let Ok(dt) = DateTime::parse_from_str(&value, format) {
+        dt.with_timezone(&Utc)
+    } else if let Ok(dt) = NaiveDateTime::parse_from_str(&value, format) {
+        dt.and_utc()
+    } else {
+        NaiveDate::parse_from_str(&value, format)
+            .map_err(|e| anyhow!("cannot parse with {:?}: {}", format, e))?
+            .and_time(Default::default())
+            .and_utc()
+    };
+
+    let value = match output {
+        DateOutput::Rfc3339 => Value::String(datetime.to_rfc3339_opts(SecondsFormat::Secs, true)),
+        DateOutput::Unix => Value::from(datetime.timestamp()),
+    };
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_date() -> Result<()> {
+        let v = normalize_date("Apr 18, 1990 (29)", "%b %d, %Y", DateOutput::Rfc3339)?;
+        assert_eq!(v, Value::String("1990-04-18T00:00:00Z".into()));
+
+        let v = normalize_date("Apr 18, 1990 (29)", "%b %d, %Y", DateOutput::Unix)?;
+        assert_eq!(v, Value::from(640396800));
+
+        let v = normalize_date("2020-01-02 03:04:05", "%Y-%m-%d %H:%M:%S", DateOutput::Unix)?;
+        assert_eq!(v, Value::from(1577934245));
+
+        assert!(normalize_date("not a date", "%b %d, %Y", DateOutput::Unix).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_outputs() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("rcli-csv-{}", std::process::id()));
+        fs::create_dir_all(dir.join("a"))?;
+        fs::create_dir_all(dir.join("b"))?;
+        let input = |name: &str| -> Result<String> {
+            let path = dir.join(name);
+            fs::write(&path, "name: a\n")?;
+            Ok(path.to_string_lossy().into_owned())
+        };
+        let inputs = [input("players.2020.csv")?, input("x.yaml")?];
+        let outputs = plan_outputs(&inputs, &dir, OutputFormat::Json, false)?;
+        assert_eq!(
+            outputs,
+            vec![dir.join("players.2020.json"), dir.join("x.json")]
+        );
+
+        let inputs = [input("a/x.csv")?, input("b/x.csv")?];
+        assert!(plan_outputs(&inputs, &dir, OutputFormat::Json, false).is_err());
+
+        // 通过不同的路径指向输入文件，加了 force 也不能覆盖
+        let inputs = [input("x.yaml")?];
+        let same_dir = dir.join("a").join("..");
+        assert!(plan_outputs(&inputs, &same_dir, OutputFormat::Yaml, true).is_err());
+
+        // 已存在的输出需要 force
+        fs::write(dir.join("x.json"), "[]")?;
+        assert!(plan_outputs(&inputs, &dir, OutputFormat::Json, false).is_err());
+        assert!(plan_outputs(&inputs, &dir, OutputFormat::Json, true).is_ok());
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_date_columns() -> Result<()> {
+        let dates = [DateColumn {
+            column: "DOB".into(),
+            format: "%Y".into(),
+        }];
+        assert!(check_date_columns("x.csv", ["Name", "DOB"], &dates).is_ok());
+        assert!(check_date_columns("x.csv", ["Name", "Dob"], &dates).is_err());
+
+        let rows = read_json_stream("{\"Name\": \"a\", \"DOB\": \"1990\"}")?;
+        assert!(check_date_columns("x.jsonl", record_columns(&rows[0]), &dates).is_ok());
+        assert!(check_date_columns("x.jsonl", record_columns(&Value::Null), &dates).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_streams() -> Result<()> {
+        let rows = read_yaml_stream("---\nname: a\n---\nname: b\n---\n- name: c\n- name: d\n")?;
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[3]["name"], "d");
+
+        let rows = read_json_stream("{\"name\": \"a\"}\n{\"name\": \"b\"}\n\n")?;
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1]["name"], "b");
+
+        let rows = read_json_stream("[{\"name\": \"a\"}, {\"name\": \"b\"}]")?;
+        assert_eq!(rows.len(), 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+use anyhow::{anyhow, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::{GenPassConfig, PasswordPolicy};
+
+// Argon2id 参数: 19 MiB 内存、2 次迭代、单线程（OWASP 推荐的最低配置）
+// 修改任何参数都会改变所有派生出来的密码，所以要跟着改 SALT_PREFIX 的版本号
+// v2: 不再依赖 rand 的 choose / shuffle，字符选择和打乱都由下面的 DeriveStream 完成
+const MEMORY_KIB: u32 = 19 * 1024;
+const ITERATIONS: u32 = 2;
+const SALT_PREFIX: &str = "rcli-derive-v2";
+// 和 genpass policy 一样，不满足策略时在同一个字节流上继续生成
+const MAX_ATTEMPTS: usize = 1000;
+
+/// 用 Argon2id 从主密码和站点信息派生 32 字节种子
+pub fn derive_seed(master: &str, site: &str, login: &str, counter: u32) -> Result<[u8; 32]> {
+    let params = Params::new(MEMORY_KIB, ITERATIONS, 1, Some(32)).map_err(|e| anyhow!("{}", e))?;
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+
+    // 用 \0 分隔，避免 "ab" + "c" 和 "a" + "bc" 得到同样的 salt
+    let salt = format!("{}\0{}\0{}\0{}", SALT_PREFIX, site, login, counter);
+    let mut seed = [0u8; 32];
+    argon2
+        .hash_password_into(master.as_bytes(), salt.as_bytes(), &mut seed)
+        .map_err(|e| anyhow!("{}", e))?;
+    Ok(seed)
+}
+
+/// 无状态密码管理: 同样的主密码 + 站点 + 登录名 + 计数器总是得到同样的密码
+/// 字符类 / 策略规则和 genpass 一样，但随机数完全来自种子派生出的字节流，结果不受 rand 版本影响
+pub fn process_genpass_derive(
+    master: &str,
+    site: &str,
+    login: &str,
+    counter: u32,
+    config: &GenPassConfig,
+    policy: Option<&PasswordPolicy>,
+) -> Result<String> {
+    let seed = derive_seed(master, site, login, counter)?;
+    let mut stream = DeriveStream::new(seed);
+    let Some(policy) = policy else {
+        return derive_password(config, &mut stream);
+    };
+
+    let mut config = config.clone();
+    policy.apply(&mut config)?;
+    for _ in 0..MAX_ATTEMPTS {
+        let pwd = derive_password(&config, &mut stream)?;
+        if policy.violations(&pwd).is_empty() {
+            return Ok(pwd);
+        }
+    }
+    Err(anyhow!(
+        "cannot derive a password satisfying the policy after {} attempts",
+        MAX_ATTEMPTS
+    ))
+}
+
+// 和 generate_password 相同的规则: 先满足每类的最少次数，再从所有字符里补齐，最后打乱
+fn derive_password(config: &GenPassConfig, stream: &mut DeriveStream) -> Result<String> {
+    let classes = config.classes()?;
+    let length = config.length as usize;
+    let required = classes.iter().map(|c| c.min).sum::<usize>();
+    if length == 0 || length < required {
+        return Err(anyhow!(
+            "length {} is too short, character sets require at least {} chars",
+            length,
+            required.max(1)
+        ));
+    }
+
+    let mut password = Vec::with_capacity(length);
+    let mut chars = Vec::new();
+    for class in &classes {
+        chars.extend_from_slice(&class.chars);
+        for _ in 0..class.min {
+            password.push(class.chars[stream.below(class.chars.len())]);
+        }
+    }
+    for _ in password.len()..length {
+        password.push(chars[stream.below(chars.len())]);
+    }
+
+    // Fisher–Yates
+    for i in (1..password.len()).rev() {
+        password.swap(i, stream.below(i + 1));
+    }
+    Ok(password.into_iter().collect())
+}
+
+/// 从种子展开的确定性字节流: 第 i 块是 HMAC-SHA256(seed, SALT_PREFIX || i)，i 为 8 字节大端计数器
+/// 格式由我们自己定义，只要 SALT_PREFIX 不变，输出就不会变
+struct DeriveStream {
+    seed: [u8; 32],
+    block: u64,
+    buf: [u8; 32],
+    pos: usize,
+}
+
+impl DeriveStream {
+    fn new(seed: [u8; 32]) -> Self {
+        Self {
+            seed,
+            block: 0,
+            buf: [0; 32],
+            pos: 32,
+        }
+    }
+
+    fn next_byte(&mut self) -> u8 {
+        if self.pos == self.buf.len() {
+            let mut mac =
+                Hmac::<Sha256>::new_from_slice(&self.seed).expect("hmac accepts any key length");
+            mac.update(SALT_PREFIX.as_bytes());
+            mac.update(&self.block.to_be_bytes());
+            self.buf = mac.finalize().into_bytes().into();
+            self.block += 1;
+            self.pos = 0;
+        }
+        self.pos += 1;
+        self.buf[self.pos - 1]
+    }
+
+    /// 均匀地取 [0, n) 里的一个数: 每次读 2 个字节，超出 n 的最大整倍数的值丢弃重取，避免取模偏差
+    fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0 && n <= 1 << 16, "range must be in 1..=65536");
+        let limit = (1 << 16) - (1 << 16) % n;
+        loop {
+            let v = u16::from_be_bytes([self.next_byte(), self.next_byte()]) as usize;
+            if v < limit {
+                return v % n;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_genpass_derive() -> Result<()> {
+        let config = GenPassConfig::new(16);
+        let a = process_genpass_derive("master", "example.com", "me", 1, &config, None)?;
+        assert_eq!(a.len(), 16);
+        // 固定的测试向量，算法变化导致密码变化时这里会失败
+        assert_eq!(a, "s9k2pHA$_&YerwQ9");
+        assert_eq!(
+            a,
+            process_genpass_derive("master", "example.com", "me", 1, &config, None)?
+        );
+        assert_ne!(
+            a,
+            process_genpass_derive("master", "example.com", "me", 2, &config, None)?
+        );
+        assert_ne!(
+            a,
+            process_genpass_derive("master", "example.org", "me", 1, &config, None)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_derive_stream_below() {
+        let mut stream = DeriveStream::new([7; 32]);
+        let mut counts = [0usize; 3];
+        for _ in 0..3000 {
+            counts[stream.below(3)] += 1;
+        }
+        assert!(
+            counts.iter().all(|c| (900..1100).contains(c)),
+            "{:?}",
+            counts
+        );
+        assert_eq!(stream.below(1), 0);
+    }
+}
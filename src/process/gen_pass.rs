@@ -1,56 +1,520 @@
+use std::collections::HashSet;
+
+use anyhow::{anyhow, Result};
 use rand::prelude::IndexedRandom;
 use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+use serde::Serialize;
+use zxcvbn::zxcvbn;
+
+use crate::{generate_password_policy, GenPassFormat, PasswordPolicy};
 
 // 字节切片
 // b"..." ---> 生成带有 'static 生命周期的字节数组引用
+// 默认去掉了容易混淆的 I O l 0，可以用 --include-ambiguous 加回来
 const UPPER: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ";
 const LOWER: &[u8] = b"abcdefghijkmnopqrstuvwxyz";
 const NUMBER: &[u8] = b"123456789";
 const SYMBOL: &[u8] = b"!@#$%^&*_";
+const AMBIGUOUS_UPPER: &[u8] = b"IO";
+const AMBIGUOUS_LOWER: &[u8] = b"l";
+const AMBIGUOUS_NUMBER: &[u8] = b"0";
+
+/// genpass 的字符集配置
+#[derive(Debug, Clone)]
+pub struct GenPassConfig {
+    pub length: u8,
+    pub upper: bool,
+    pub lower: bool,
+    pub number: bool,
+    pub symbol: bool,
+    /// 自定义字符集，设置后替代上面四类字符
+    pub charset: Option<String>,
+    /// 替换默认的符号集合
+    pub symbols: Option<String>,
+    /// 需要排除的字符
+    pub exclude: String,
+    pub include_ambiguous: bool,
+    /// 每类字符最少出现的次数，None 表示启用时至少 1 个
+    pub min_upper: Option<usize>,
+    pub min_lower: Option<usize>,
+    pub min_digits: Option<usize>,
+    pub min_symbols: Option<usize>,
+}
+
+/// genpass --json 的输出
+#[derive(Debug, Serialize)]
+pub struct PasswordReport {
+    pub password: String,
+    pub entropy_bits: f64,
+    pub zxcvbn_score: u8,
+    /// 离线慢哈希（每秒 1 万次）下的破解时间
+    pub crack_time: String,
+}
+
+/// 一类字符以及它在密码里最少出现的次数
+#[derive(Debug, Clone)]
+pub struct CharClass {
+    pub name: &'static str,
+    pub chars: Vec<char>,
+    pub min: usize,
+}
+
+impl GenPassConfig {
+    /// 默认配置: 四类字符都启用，每类至少一个
+    pub fn new(length: u8) -> Self {
+        Self {
+            length,
+            upper: true,
+            lower: true,
+            number: true,
+            symbol: true,
+            charset: None,
+            symbols: None,
+            exclude: String::new(),
+            include_ambiguous: false,
+            min_upper: None,
+            min_lower: None,
+            min_digits: None,
+            min_symbols: None,
+        }
+    }
+
+    /// 按配置算出启用的字符类，排除字符之后为空的类会报错
+    pub fn classes(&self) -> Result<Vec<CharClass>> {
+        if let Some(charset) = &self.charset {
+            let class = self.class("charset", charset.as_bytes(), &[], true, Some(1))?;
+            return Ok(class.into_iter().collect());
+        }
+
+        let symbols = self.symbols.as_ref().map_or(SYMBOL, |s| s.as_bytes());
+        let classes = [
+            self.class(
+                "uppercase",
+                UPPER,
+                AMBIGUOUS_UPPER,
+                self.upper,
+                self.min_upper,
+            )?,
+            self.class(
+                "lowercase",
+                LOWER,
+                AMBIGUOUS_LOWER,
+                self.lower,
+                self.min_lower,
+            )?,
+            self.class(
+                "number",
+                NUMBER,
+                AMBIGUOUS_NUMBER,
+                self.number,
+                self.min_digits,
+            )?,
+            self.class("symbol", symbols, &[], self.symbol, self.min_symbols)?,
+        ];
+        let mut classes = classes.into_iter().flatten().collect::<Vec<_>>();
+        // --symbols 可能和字母数字重叠，重复的字符只留在前面的类里
+        // 否则它会被多抽中几次，熵也会按不相交的类算多了
+        let mut seen = HashSet::new();
+        for class in classes.iter_mut() {
+            class.chars.retain(|c| seen.insert(*c));
+            if class.chars.is_empty() {
+                return Err(anyhow!(
+                    "{} character set only contains characters from other sets",
+                    class.name
+                ));
+            }
+        }
+        if classes.is_empty() {
+            return Err(anyhow!("at least one character set must be enabled"));
+        }
+        Ok(classes)
+    }
+
+    /// 所有启用的字符合在一起，填充剩余长度时从这里面选
+    pub fn pool(&self) -> Result<Vec<char>> {
+        Ok(self.classes()?.into_iter().flat_map(|c| c.chars).collect())
+    }
+
+    /// 精确的熵: 满足每类最少次数的所有密码个数取 log2
+    /// 没有最少次数限制时就是 length * log2(pool)，有限制时会略小一些
+    pub fn entropy(&self) -> Result<f64> {
+        let classes = self.classes()?;
+        let length = self.length as usize;
+        // 个数 = L! * sum(prod(size^k / k!))，k 是每类字符出现的次数，和为 L 且不小于 min
+        // 数字会非常大，在 log2 空间里做动态规划: dp[j] 表示前几类字符占了 j 个位置
+        let log_fact = (0..=length)
+            .scan(0.0, |acc, n| {
+                if n > 0 {
+                    *acc += (n as f64).log2();
+                }
+                Some(*acc)
+            })
+            .collect::<Vec<_>>();
+        let mut dp = vec![f64::NEG_INFINITY; length + 1];
+        dp[0] = 0.0;
+        for class in &classes {
+            let size = (class.chars.len() as f64).log2();
+            let mut next = vec![f64::NEG_INFINITY; length + 1];
+            for (j, slot) in next.iter_mut().enumerate() {
+                let terms = (class.min..=j)
+                    .map(|k| dp[j - k] + k as f64 * size - log_fact[k])
+                    .collect::<Vec<_>>();
+                *slot = log2_sum(&terms);
+            }
+            dp = next;
+        }
+        if dp[length] == f64::NEG_INFINITY {
+            let required = classes.iter().map(|c| c.min).sum::<usize>();
+            return Err(anyhow!(
+                "length {} is too short, character sets require at least {} chars",
+                length,
+                required.max(1)
+            ));
+        }
+        Ok(log_fact[length] + dp[length])
+    }
+
+    /// 找到熵不小于 bits 的最短长度，--entropy 128 用它来决定长度
+    pub fn length_for_entropy(&self, bits: f64) -> Result<u8> {
+        for length in 1..=u8::MAX {
+            let config = GenPassConfig {
+                length,
+                ..self.clone()
+            };
+            // 长度不够最少次数之和时 entropy 会报错，跳过继续加长
+            if config.entropy().is_ok_and(|e| e >= bits) {
+                return Ok(length);
+            }
+        }
+        Err(anyhow!(
+            "cannot reach {} bits of entropy within {} chars",
+            bits,
+            u8::MAX
+        ))
+    }
+
+    fn class(
+        &self,
+        name: &'static str,
+        base: &[u8],
+        ambiguous: &[u8],
+        enabled: bool,
+        min: Option<usize>,
+    ) -> Result<Option<CharClass>> {
+        if !enabled {
+            return match min {
+                Some(n) if n > 0 => Err(anyhow!("{} is disabled but requires {} chars", name, n)),
+                _ => Ok(None),
+            };
+        }
+
+        let mut chars = String::from_utf8_lossy(base).chars().collect::<Vec<_>>();
+        if self.include_ambiguous {
+            chars.extend(ambiguous.iter().map(|c| *c as char));
+        }
+        chars.retain(|c| !self.exclude.contains(*c));
+        // 自定义字符集里可能有重复字符，去重之后每个字符的概率才相同
+        chars.sort_unstable();
+        chars.dedup();
+
+        if chars.is_empty() {
+            return Err(anyhow!("{} character set is empty after exclusions", name));
+        }
+        Ok(Some(CharClass {
+            name,
+            chars,
+            min: min.unwrap_or(1),
+        }))
+    }
+}
+
+pub fn process_genpass(config: &GenPassConfig) -> Result<String> {
+    generate_password(config, &mut rand::rng())
+}
+
+/// 使用指定的随机数生成器生成密码，derive 模式用确定性的 rng 得到可复现的结果
+pub fn generate_password<R: Rng + ?Sized>(config: &GenPassConfig, rng: &mut R) -> Result<String> {
+    let classes = config.classes()?;
+    let length = config.length as usize;
 
-pub fn process_genpass(
-    length: u8,
-    upper: bool,
-    lower: bool,
-    number: bool,
-    symbol: bool,
-) -> anyhow::Result<String> {
-    let mut rng = rand::rng();
-    let mut password = Vec::new();
+    // 每类字符要满足最少次数，所以长度不能小于最少次数之和
+    let required = classes.iter().map(|c| c.min).sum::<usize>();
+    if length == 0 || length < required {
+        return Err(anyhow!(
+            "length {} is too short, character sets require at least {} chars",
+            length,
+            required.max(1)
+        ));
+    }
+
+    let mut password = Vec::with_capacity(length);
     let mut chars = Vec::new();
 
-    if upper {
-        chars.extend_from_slice(UPPER);
+    for class in &classes {
+        chars.extend_from_slice(&class.chars);
 
         // choose 是 rand 库提供的“从切片中随机挑选一个元素”的方法，属于一个扩展 trait 的方法，不是标准切片本身的固有方法
         // rand 0.9 版本中，choose 属于 IndexedRandom 这个 trait
         // 要想在 &[u8] 或 Vec 上调用 choose，必须把该 trait 引入作用域：use rand::seq::IndexedRandom;
-        password.push(*UPPER.choose(&mut rng).expect("UPPER won't be empty"));
-    }
-    if lower {
-        chars.extend_from_slice(LOWER);
-        password.push(*LOWER.choose(&mut rng).expect("LOWER won't be empty"));
-    }
-    if number {
-        chars.extend_from_slice(NUMBER);
-        password.push(*NUMBER.choose(&mut rng).expect("NUMBER won't be empty"));
-    }
-    if symbol {
-        chars.extend_from_slice(SYMBOL);
-        password.push(*SYMBOL.choose(&mut rng).expect("SYMBOL won't be empty"));
+        for _ in 0..class.min {
+            password.push(*class.chars.choose(rng).expect("class won't be empty"));
+        }
     }
 
-    for _ in 0..(length - password.len() as u8) {
+    for _ in password.len()..length {
         let c = chars
-            .choose(&mut rng)
+            .choose(rng)
             .expect("chars won't be empty in this context");
-        // c 是一个引用（类型是 &u8），而 Vec::push 需要的是一个值（类型是 u8），所以要用 *c 把引用“解引用”成实际的字节值
+        // c 是一个引用（类型是 &char），而 Vec::push 需要的是一个值（类型是 char），所以要用 *c 把引用“解引用”成实际的值
         password.push(*c);
     }
 
-    password.shuffle(&mut rng);
+    password.shuffle(rng);
 
-    let pwd = String::from_utf8(password)?;
+    Ok(password.into_iter().collect())
+}
+
+/// 批量生成密码，unique 时保证结果里没有重复
+pub fn process_genpass_batch(
+    config: &GenPassConfig,
+    policy: Option<&PasswordPolicy>,
+    count: usize,
+    unique: bool,
+    rng: &mut dyn RngCore,
+) -> Result<Vec<String>> {
+    let mut passwords = Vec::with_capacity(count);
+    let mut seen = HashSet::new();
+    // 字符集很小时可能根本凑不出 count 个不同的密码，限制尝试次数避免死循环
+    let mut attempts = 0;
+    while passwords.len() < count {
+        if attempts >= count * 100 + 1000 {
+            return Err(anyhow!(
+                "cannot generate {} unique passwords, only got {}",
+                count,
+                passwords.len()
+            ));
+        }
+        attempts += 1;
 
-    Ok(pwd)
+        let pwd = match policy {
+            Some(policy) => generate_password_policy(config, policy, rng)?,
+            None => generate_password(config, rng)?,
+        };
+        if unique && !seen.insert(pwd.clone()) {
+            continue;
+        }
+        passwords.push(pwd);
+    }
+    Ok(passwords)
+}
+
+/// 按输出格式渲染密码列表，csv 额外带上 zxcvbn 评分
+pub fn format_passwords(passwords: &[String], format: GenPassFormat) -> Result<String> {
+    let content = match format {
+        GenPassFormat::Plain => passwords.join("\n"),
+        GenPassFormat::Json => serde_json::to_string_pretty(passwords)?,
+        GenPassFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            writer.write_record(["password", "score"])?;
+            for pwd in passwords {
+                let score = u8::from(zxcvbn(pwd, &[]).score());
+                writer.write_record([pwd.as_str(), &score.to_string()])?;
+            }
+            String::from_utf8(writer.into_inner()?)?
+                .trim_end()
+                .to_string()
+        }
+    };
+    Ok(content)
+}
+
+/// 生成 --json 输出需要的熵和 zxcvbn 评估
+pub fn password_report(password: &str, entropy_bits: f64) -> PasswordReport {
+    let estimate = zxcvbn(password, &[]);
+    PasswordReport {
+        password: password.to_string(),
+        entropy_bits,
+        zxcvbn_score: estimate.score().into(),
+        crack_time: estimate
+            .crack_times()
+            .offline_slow_hashing_1e4_per_second()
+            .to_string(),
+    }
+}
+
+/// log2(2^a + 2^b + ...)，先减去最大值避免溢出
+fn log2_sum(terms: &[f64]) -> f64 {
+    let max = terms.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    if max == f64::NEG_INFINITY {
+        return max;
+    }
+    max + terms.iter().map(|t| (t - max).exp2()).sum::<f64>().log2()
+}
+
+/// 从 pool_size 个元素里独立随机选 length 次的熵: length * log2(pool_size)
+pub fn entropy_bits(pool_size: usize, length: usize) -> f64 {
+    length as f64 * (pool_size as f64).log2()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(length: u8, upper: bool, lower: bool, number: bool, symbol: bool) -> GenPassConfig {
+        GenPassConfig {
+            upper,
+            lower,
+            number,
+            symbol,
+            ..GenPassConfig::new(length)
+        }
+    }
+
+    #[test]
+    fn test_process_genpass_combinations() -> Result<()> {
+        // 0b0000 ~ 0b1111 覆盖四类字符所有开关组合
+        for flags in 1..16u8 {
+            let (upper, lower, number, symbol) = (
+                flags & 1 != 0,
+                flags & 2 != 0,
+                flags & 4 != 0,
+                flags & 8 != 0,
+            );
+            let pwd = process_genpass(&config(16, upper, lower, number, symbol))?;
+            assert_eq!(pwd.len(), 16);
+            for (enabled, set) in [
+                (upper, UPPER),
+                (lower, LOWER),
+                (number, NUMBER),
+                (symbol, SYMBOL),
+            ] {
+                assert_eq!(pwd.bytes().any(|c| set.contains(&c)), enabled, "{}", pwd);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_generate_password_seeded() -> Result<()> {
+        // 固定 seed 得到固定的密码，ChaCha20 的输出不会随 rand 版本变化
+        let mut rng = crate::build_rng(Some(42));
+        let passwords = process_genpass_batch(&GenPassConfig::new(16), None, 2, false, &mut rng)?;
+        assert_eq!(passwords, ["7%MGMZ6^vnLWA4%f", "E3nJ@&1Ycz_KUzus"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_genpass_entropy() -> Result<()> {
+        // 没有最少次数限制时等于 length * log2(pool)
+        let digits = GenPassConfig {
+            charset: Some("0123456789".into()),
+            ..GenPassConfig::new(8)
+        };
+        assert!((digits.entropy()? - entropy_bits(10, 8)).abs() < 1e-9);
+
+        // 两类各 1 个字符、长度 2、至少各一个: 只有 "ab" "ba" 两种
+        let two = GenPassConfig {
+            exclude: "BCDEFGHJKLMNPQRSTUVWXYZbcdefghijkmnopqrstuvwxyz".into(),
+            ..config(2, true, true, false, false)
+        };
+        assert!((two.entropy()? - 1.0).abs() < 1e-9);
+
+        let default = GenPassConfig::new(16);
+        let entropy = default.entropy()?;
+        assert!(entropy < entropy_bits(default.pool()?.len(), 16));
+        assert!(entropy > 96.0);
+
+        let length = default.length_for_entropy(128.0)?;
+        assert_eq!(length, 22);
+        assert!(default.length_for_entropy(10_000.0).is_err());
+
+        // --symbols 和小写字母重叠时只剩 25 个小写 + "-"
+        let overlap = GenPassConfig {
+            symbols: Some("abc-".into()),
+            min_lower: Some(0),
+            min_symbols: Some(0),
+            ..config(10, false, true, false, true)
+        };
+        assert_eq!(overlap.pool()?.len(), 26);
+        assert!((overlap.entropy()? - entropy_bits(26, 10)).abs() < 1e-9);
+
+        let covered = GenPassConfig {
+            symbols: Some("abc".into()),
+            ..overlap
+        };
+        assert!(covered.classes().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_genpass_invalid() {
+        assert!(process_genpass(&config(16, false, false, false, false)).is_err());
+        assert!(process_genpass(&config(3, true, true, true, true)).is_err());
+        assert!(process_genpass(&config(0, true, false, false, false)).is_err());
+        assert_eq!(
+            process_genpass(&config(4, true, true, true, true))
+                .unwrap()
+                .len(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_process_genpass_charsets() -> Result<()> {
+        let pwd = process_genpass(&GenPassConfig {
+            charset: Some("abc".into()),
+            ..GenPassConfig::new(20)
+        })?;
+        assert!(pwd.chars().all(|c| "abc".contains(c)));
+
+        let pwd = process_genpass(&GenPassConfig {
+            symbols: Some("-+".into()),
+            exclude: "ABCabc123".into(),
+            min_digits: Some(3),
+            ..GenPassConfig::new(20)
+        })?;
+        assert!(pwd.chars().all(|c| !"ABCabc123!@#".contains(c)));
+        assert!(pwd.chars().filter(|c| c.is_ascii_digit()).count() >= 3);
+
+        let pool = GenPassConfig {
+            include_ambiguous: true,
+            ..GenPassConfig::new(16)
+        }
+        .pool()?;
+        assert!("IOl0".chars().all(|c| pool.contains(&c)));
+
+        let empty = GenPassConfig {
+            exclude: "123456789".into(),
+            ..GenPassConfig::new(16)
+        };
+        assert!(process_genpass(&empty).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_genpass_batch() -> Result<()> {
+        let passwords =
+            process_genpass_batch(&GenPassConfig::new(12), None, 50, true, &mut rand::rng())?;
+        assert_eq!(passwords.len(), 50);
+        assert_eq!(passwords.iter().collect::<HashSet<_>>().len(), 50);
+
+        // "ab" 长度 1 只有两种结果
+        let tiny = GenPassConfig {
+            charset: Some("ab".into()),
+            ..GenPassConfig::new(1)
+        };
+        let mut rng = rand::rng();
+        assert!(process_genpass_batch(&tiny, None, 3, true, &mut rng).is_err());
+        assert_eq!(
+            process_genpass_batch(&tiny, None, 3, false, &mut rng)?.len(),
+            3
+        );
+
+        let csv = format_passwords(&["abc".into(), "x$Yz9!kQ".into()], GenPassFormat::Csv)?;
+        assert!(csv.starts_with("password,score\nabc,0\n"));
+        let json = format_passwords(&["abc".into()], GenPassFormat::Json)?;
+        assert_eq!(serde_json::from_str::<Vec<String>>(&json)?, vec!["abc"]);
+        Ok(())
+    }
 }
@@ -0,0 +1,219 @@
+use std::{fs, path::Path};
+
+use anyhow::{anyhow, Result};
+use csv::{Reader, Writer};
+use serde_json::Value;
+
+use super::text::{Black3, KeyLoader};
+use crate::{par_map, MaskMode};
+
+/// 对敏感字段做脱敏，hash / fake 使用 Black3 key 做 keyed hash，
+/// 同样的输入总是得到同样的结果，方便脱敏后的数据还能做关联
+pub struct Masker {
+    mode: MaskMode,
+    key: Option<Black3>,
+}
+
+impl Masker {
+    pub fn new(mode: MaskMode, key: Option<Black3>) -> Result<Self> {
+        if matches!(mode, MaskMode::Hash | MaskMode::Fake) && key.is_none() {
+            return Err(anyhow!("mask mode {} requires a black3 key", mode));
+        }
+        Ok(Self { mode, key })
+    }
+
+    pub fn load(mode: MaskMode, key: Option<&str>) -> Result<Self> {
+        let key = key.map(Black3::load).transpose()?;
+        Self::new(mode, key)
+    }
+
+    pub fn mask(&self, value: &str) -> String {
+        // 空值不需要脱敏，也避免把所有空值都映射成同一个假名
+        if value.is_empty() {
+            return String::new();
+        }
+        match self.mode {
+            MaskMode::Hash => self.digest(value)[..16].to_string(),
+            MaskMode::Redact => "[REDACTED]".to_string(),
+            MaskMode::Fake => self.fake(value),
+            MaskMode::Partial => partial(value),
+        }
+    }
+
+    fn digest(&self, value: &str) -> String {
+        let key = self.key.as_ref().expect("key checked in Masker::new");
+        blake3::keyed_hash(&key.key, value.as_bytes())
+            .to_hex()
+            .to_string()
+    }
+
+    fn fake(&self, value: &str) -> String {
+        let digest = self.digest(value);
+        if value.contains('@') {
+            return format!("user_{}@example.com", &digest[..8]);
+        }
+        if is_phone(value) {
+            // 保留原来的格式（+、空格、-），只替换数字
+            let mut digits = digest.bytes().map(|b| (b % 10 + b'0') as char);
+            return value
+                .chars()
+                .map(|c| match c.is_ascii_digit() {
+                    true => digits.next().unwrap_or('0'),
+                    false => c,
+                })
+                .collect();
+        }
+        format!("user_{}", &digest[..8])
+    }
+}
+
+// 邮箱保留首字母和域名，其他值只保留最后 4 个字符
+fn partial(value: &str) -> String {
+    if let Some((name, domain)) = value.split_once('@') {
+        let first = name.chars().next().unwrap_or('*');
+        return format!("{}***@{}", first, domain);
+    }
+    let len = value.chars().count();
+    let keep = if len > 4 { 4 } else { 0 };
+    value
+        .chars()
+        .enumerate()
+        .map(|(i, c)| if i < len - keep { '*' } else { c })
+        .collect()
+}
+
+fn is_phone(value: &str) -> bool {
+    value.chars().any(|c| c.is_ascii_digit())
+        && value
+            .chars()
+            .all(|c| c.is_ascii_digit() || " +-().".contains(c))
+}
+
+pub fn process_csv_mask(
+    input: &str,
+    output: &str,
+    columns: &[String],
+    mode: MaskMode,
+    key: Option<&str>,
+    jobs: usize,
+) -> Result<()> {
+    let masker = Masker::load(mode, key)?;
+    let is_json = Path::new(input)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+
+    if is_json {
+        mask_json(input, output, columns, &masker, jobs)
+    } else {
+        mask_csv(input, output, columns, &masker, jobs)
+    }
+}
+
+fn mask_csv(
+    input: &str,
+    output: &str,
+    columns: &[String],
+    masker: &Masker,
+    jobs: usize,
+) -> Result<()> {
+    let mut reader = Reader::from_path(input)?;
+    let headers = reader.headers()?.clone();
+    let indexes = columns
+        .iter()
+        .map(|col| {
+            headers
+                .iter()
+                .position(|h| h == col)
+                .ok_or_else(|| anyhow!("column {:?} not found in {}", col, input))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let records = reader.records().collect::<Result<Vec<_>, _>>()?;
+    let rows = par_map(jobs, &records, |_, record| {
+        record
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                if indexes.contains(&i) {
+                    masker.mask(v)
+                } else {
+                    v.to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+    })?;
+
+    let mut writer = Writer::from_path(output)?;
+    writer.write_record(&headers)?;
+    for row in rows {
+        writer.write_record(row)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+// json 输入要求是对象数组: [{"email": "..."}, ...]
+fn mask_json(
+    input: &str,
+    output: &str,
+    columns: &[String],
+    masker: &Masker,
+    jobs: usize,
+) -> Result<()> {
+    let content = fs::read_to_string(input)?;
+    let rows: Vec<Value> = serde_json::from_str(&content)?;
+    let rows = par_map(jobs, &rows, |_, row| mask_object(row, columns, masker))?
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+    fs::write(output, serde_json::to_string_pretty(&rows)?)?;
+    Ok(())
+}
+
+fn mask_object(row: &Value, columns: &[String], masker: &Masker) -> Result<Value> {
+    let Value::Object(row) = row else {
+        return Err(anyhow!("json input must be an array of objects"));
+    };
+    let mut row = row.clone();
+    for col in columns {
+        if let Some(v) = row.get_mut(col) {
+            let raw = match &*v {
+                Value::Null => continue,
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            *v = Value::String(masker.mask(&raw));
+        }
+    }
+    Ok(Value::Object(row))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask_is_deterministic() -> Result<()> {
+        let masker = Masker::load(MaskMode::Hash, Some("fixtures/black3.txt"))?;
+        let a = masker.mask("alice@example.org");
+        assert_eq!(a, masker.mask("alice@example.org"));
+        assert_ne!(a, masker.mask("bob@example.org"));
+        assert_eq!(a.len(), 16);
+        Ok(())
+    }
+
+    #[test]
+    fn test_mask_modes() -> Result<()> {
+        let fake = Masker::load(MaskMode::Fake, Some("fixtures/black3.txt"))?;
+        assert!(fake.mask("alice@example.org").ends_with("@example.com"));
+        let phone = fake.mask("+39 333-1234567");
+        assert_eq!(phone.len(), "+39 333-1234567".len());
+        assert_eq!(&phone[..1], "+");
+
+        let partial = Masker::new(MaskMode::Partial, None)?;
+        assert_eq!(partial.mask("alice@example.org"), "a***@example.org");
+        assert_eq!(partial.mask("3331234567"), "******4567");
+
+        assert!(Masker::new(MaskMode::Hash, None).is_err());
+        Ok(())
+    }
+}
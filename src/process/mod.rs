@@ -1,9 +1,51 @@
+mod apikey;
 mod b64;
+mod codec;
 mod csv_convert;
+mod derive;
 mod gen_pass;
+mod mask;
+mod otp;
+mod pass_check;
+mod passphrase;
+mod pattern;
+mod pin;
+mod policy;
+mod shamir;
 mod text;
+mod token;
+mod vault;
 
-pub use b64::{process_decode, process_encode};
-pub use csv_convert::process_csv;
-pub use gen_pass::process_genpass;
-pub use text::{process_text_generate_keye, process_text_sign, process_text_verify};
+pub use apikey::{generate_apikey, process_apikey, process_apikey_verify, verify_apikey};
+pub use b64::{decode_stream, encode_stream, process_decode, process_encode};
+pub use codec::{decode_str, encode_bytes, process_codec_decode, process_codec_encode};
+pub use csv_convert::{
+    normalize_date, process_csv, process_csv_each, read_json_stream, read_yaml_stream,
+};
+pub use derive::{derive_seed, process_genpass_derive};
+pub use gen_pass::{
+    entropy_bits, format_passwords, generate_password, password_report, process_genpass,
+    process_genpass_batch, CharClass, GenPassConfig, PasswordReport,
+};
+pub use mask::{process_csv_mask, Masker};
+pub use otp::{
+    generate_otp_secret, hotp, process_otp_generate, process_otp_new, process_otp_verify, OtpConfig,
+};
+pub use pass_check::{check_strength, process_pass_check, StrengthReport};
+pub use passphrase::{load_wordlist, process_passphrase};
+pub use pattern::{
+    entropy_label, generate_pattern, pattern_entropy, process_pattern, pronounceable_pattern,
+};
+pub use pin::{process_pin, weak_pin_patterns};
+pub use policy::{
+    generate_password_policy, process_genpass_policy, process_policy_check, CharKind,
+    PasswordPolicy,
+};
+pub use shamir::{
+    combine_shares, process_secret_combine, process_secret_split, split_secret, Share,
+};
+pub use text::{
+    process_text_generate_keye, process_text_migrate_key, process_text_sign, process_text_verify,
+};
+pub use token::{generate_token, process_token};
+pub use vault::{write_private, Vault, VaultEntry};
@@ -0,0 +1,255 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Result};
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+
+use crate::OtpAlgorithm;
+
+/// otp 的算法参数，生成和校验两端必须一致
+#[derive(Debug, Clone, Copy)]
+pub struct OtpConfig {
+    pub algorithm: OtpAlgorithm,
+    pub digits: u32,
+    /// TOTP 时间步长，单位秒
+    pub period: u64,
+}
+
+impl Default for OtpConfig {
+    /// 大多数验证器 app 只支持的默认值: SHA1，6 位，30 秒
+    fn default() -> Self {
+        Self {
+            algorithm: OtpAlgorithm::Sha1,
+            digits: 6,
+            period: 30,
+        }
+    }
+}
+
+/// 生成 TOTP 码，counter 不为空时按 HOTP 生成
+pub fn process_otp_generate(
+    secret: &str,
+    config: &OtpConfig,
+    time: Option<u64>,
+    counter: Option<u64>,
+) -> Result<String> {
+    let key = decode_secret(secret)?;
+    let counter = match counter {
+        Some(counter) => counter,
+        None => time_step(time, config.period)?,
+    };
+    hotp(&key, counter, config.algorithm, config.digits)
+}
+
+// 允许的最大偏差步数，和 cli 的 --window 范围一致
+const MAX_WINDOW: u64 = 10;
+
+/// 校验 TOTP / HOTP 码，允许前后 window 个时间步（或计数）的偏差
+pub fn process_otp_verify(
+    secret: &str,
+    code: &str,
+    config: &OtpConfig,
+    time: Option<u64>,
+    counter: Option<u64>,
+    window: u64,
+) -> Result<bool> {
+    if window > MAX_WINDOW {
+        return Err(anyhow!("window must be at most {} steps", MAX_WINDOW));
+    }
+    let key = decode_secret(secret)?;
+    let current = match counter {
+        Some(counter) => counter,
+        None => time_step(time, config.period)?,
+    };
+    let code = code.trim();
+    for c in current.saturating_sub(window)..=current.saturating_add(window) {
+        let expected = hotp(&key, c, config.algorithm, config.digits)?;
+        if constant_time_eq(expected.as_bytes(), code.as_bytes()) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// 生成新的 base32 secret 和 otpauth:// URI，URI 可以直接生成二维码给验证器 app 扫描
+pub fn process_otp_new(
+    issuer: &str,
+    account: &str,
+    config: &OtpConfig,
+    bytes: usize,
+) -> Result<(String, String)> {
+    generate_otp_secret(issuer, account, config, bytes, &mut rand::rng())
+}
+
+pub fn generate_otp_secret<R: Rng + ?Sized>(
+    issuer: &str,
+    account: &str,
+    config: &OtpConfig,
+    bytes: usize,
+    rng: &mut R,
+) -> Result<(String, String)> {
+    // RFC 4226 要求 secret 至少 128 bit，推荐 160 bit
+    if bytes < 16 {
+        return Err(anyhow!("secret must be at least 16 bytes"));
+    }
+    let mut key = vec![0u8; bytes];
+    rng.fill(key.as_mut_slice());
+    let secret = BASE32_NOPAD.encode(&key);
+    let uri = format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm={}&digits={}&period={}",
+        url_encode(issuer),
+        url_encode(account),
+        secret,
+        url_encode(issuer),
+        config.algorithm,
+        config.digits,
+        config.period
+    );
+    Ok((secret, uri))
+}
+
+/// RFC 4226: HMAC(key, counter) 之后动态截断，取低 digits 位十进制
+pub fn hotp(key: &[u8], counter: u64, algorithm: OtpAlgorithm, digits: u32) -> Result<String> {
+    if !(6..=8).contains(&digits) {
+        return Err(anyhow!("digits must be between 6 and 8"));
+    }
+    let msg = counter.to_be_bytes();
+    let hash = match algorithm {
+        OtpAlgorithm::Sha1 => hmac::<Hmac<Sha1>>(key, &msg)?,
+        OtpAlgorithm::Sha256 => hmac::<Hmac<Sha256>>(key, &msg)?,
+        OtpAlgorithm::Sha512 => hmac::<Hmac<Sha512>>(key, &msg)?,
+    };
+    let offset = (hash[hash.len() - 1] & 0x0F) as usize;
+    let bin = u32::from_be_bytes(hash[offset..offset + 4].try_into()?) & 0x7FFF_FFFF;
+    let code = bin % 10u32.pow(digits);
+    Ok(format!("{:0width$}", code, width = digits as usize))
+}
+
+fn hmac<M: Mac + hmac::digest::KeyInit>(key: &[u8], msg: &[u8]) -> Result<Vec<u8>> {
+    let mut mac = <M as Mac>::new_from_slice(key)?;
+    mac.update(msg);
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+/// RFC 6238: T = (当前时间 - T0) / period，T0 = 0
+fn time_step(time: Option<u64>, period: u64) -> Result<u64> {
+    if period == 0 {
+        return Err(anyhow!("period must be greater than 0"));
+    }
+    let now = match time {
+        Some(time) => time,
+        None => SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+    };
+    Ok(now / period)
+}
+
+/// 验证器 app 展示的 secret 常常带空格、小写或者 = 填充，统一处理之后再解码
+fn decode_secret(secret: &str) -> Result<Vec<u8>> {
+    let secret = secret
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '=')
+        .collect::<String>()
+        .to_uppercase();
+    let key = BASE32_NOPAD
+        .decode(secret.as_bytes())
+        .map_err(|e| anyhow!("invalid base32 secret: {}", e))?;
+    if key.is_empty() {
+        return Err(anyhow!("secret is empty"));
+    }
+    Ok(key)
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn url_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hotp_rfc4226() -> Result<()> {
+        // RFC 4226 Appendix D
+        let key = b"12345678901234567890";
+        let expected = [
+            "755224", "287082", "359152", "969429", "338314", "254676", "287922", "162583",
+            "399871", "520489",
+        ];
+        for (counter, code) in expected.iter().enumerate() {
+            assert_eq!(hotp(key, counter as u64, OtpAlgorithm::Sha1, 6)?, *code);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_totp_rfc6238() -> Result<()> {
+        // RFC 6238 Appendix B，三种算法用不同长度的 key
+        let cases = [
+            (OtpAlgorithm::Sha1, &b"12345678901234567890"[..]),
+            (OtpAlgorithm::Sha256, b"12345678901234567890123456789012"),
+            (
+                OtpAlgorithm::Sha512,
+                b"1234567890123456789012345678901234567890123456789012345678901234",
+            ),
+        ];
+        let expected = [
+            (59, ["94287082", "46119246", "90693936"]),
+            (1111111109, ["07081804", "68084774", "25091201"]),
+            (1234567890, ["89005924", "91819424", "93441116"]),
+            (20000000000, ["65353130", "77737706", "47863826"]),
+        ];
+        for (time, codes) in expected {
+            for ((algorithm, key), code) in cases.iter().zip(codes) {
+                let secret = BASE32_NOPAD.encode(key);
+                let config = OtpConfig {
+                    algorithm: *algorithm,
+                    digits: 8,
+                    period: 30,
+                };
+                let got = process_otp_generate(&secret, &config, Some(time), None)?;
+                assert_eq!(got, code, "{} {}", algorithm, time);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_otp_verify_and_new() -> Result<()> {
+        let config = OtpConfig::default();
+        let (secret, uri) = process_otp_new("rcli", "me@example.com", &config, 20)?;
+        assert_eq!(secret.len(), 32);
+        assert!(uri.starts_with("otpauth://totp/rcli:me%40example.com?secret="));
+        assert!(uri.ends_with("&issuer=rcli&algorithm=SHA1&digits=6&period=30"));
+
+        let code = process_otp_generate(&secret, &config, Some(1000), None)?;
+        let verify =
+            |time, window| process_otp_verify(&secret, &code, &config, Some(time), None, window);
+        assert!(verify(1000, 0)?);
+        assert!(verify(1030, 1)?);
+        assert!(!verify(1030, 0)?);
+        assert!(verify(1000, 4_000_000_000).is_err());
+
+        // 小写、带空格的 secret 也能识别
+        let spaced = format!("{} {}", &secret[..16], &secret[16..]).to_lowercase();
+        assert_eq!(
+            process_otp_generate(&spaced, &config, Some(1000), None)?,
+            code
+        );
+        assert!(process_otp_new("rcli", "me", &config, 8).is_err());
+        Ok(())
+    }
+}
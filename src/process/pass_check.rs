@@ -0,0 +1,125 @@
+use std::io::Read;
+
+use anyhow::Result;
+use serde::Serialize;
+use zxcvbn::{matching::patterns::MatchPattern, zxcvbn};
+
+use crate::get_reader;
+
+/// 单个密码的强度评估结果，不包含密码本身
+#[derive(Debug, Serialize)]
+pub struct StrengthReport {
+    pub line: usize,
+    pub score: u8,
+    pub guesses_log10: f64,
+    pub crack_times: CrackTimesReport,
+    pub patterns: Vec<PatternReport>,
+    pub warning: Option<String>,
+    pub suggestions: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CrackTimesReport {
+    pub online_throttling: String,
+    pub online_no_throttling: String,
+    pub offline_slow_hashing: String,
+    pub offline_fast_hashing: String,
+}
+
+/// 命中的模式只记录类型和长度，单个字典词或暴力匹配时 token 就是密码本身，不能输出
+#[derive(Debug, Serialize)]
+pub struct PatternReport {
+    pub pattern: &'static str,
+    pub length: usize,
+}
+
+pub fn check_strength(line: usize, password: &str, user_inputs: &[&str]) -> StrengthReport {
+    let estimate = zxcvbn(password, user_inputs);
+    let times = estimate.crack_times();
+    let patterns = estimate
+        .sequence()
+        .iter()
+        .map(|m| PatternReport {
+            pattern: pattern_name(&m.pattern),
+            length: m.token.chars().count(),
+        })
+        .collect();
+    let (warning, suggestions) = match estimate.feedback() {
+        Some(feedback) => (
+            feedback.warning().map(|w| w.to_string()),
+            feedback
+                .suggestions()
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+        ),
+        None => (None, Vec::new()),
+    };
+
+    StrengthReport {
+        line,
+        score: estimate.score().into(),
+        guesses_log10: estimate.guesses_log10(),
+        crack_times: CrackTimesReport {
+            online_throttling: times.online_throttling_100_per_hour().to_string(),
+            online_no_throttling: times.online_no_throttling_10_per_second().to_string(),
+            offline_slow_hashing: times.offline_slow_hashing_1e4_per_second().to_string(),
+            offline_fast_hashing: times.offline_fast_hashing_1e10_per_second().to_string(),
+        },
+        patterns,
+        warning,
+        suggestions,
+    }
+}
+
+fn pattern_name(pattern: &MatchPattern) -> &'static str {
+    match pattern {
+        MatchPattern::Dictionary(_) => "dictionary",
+        MatchPattern::Spatial(_) => "spatial",
+        MatchPattern::Repeat(_) => "repeat",
+        MatchPattern::Sequence(_) => "sequence",
+        MatchPattern::Regex(_) => "regex",
+        MatchPattern::Date(_) => "date",
+        MatchPattern::BruteForce => "bruteforce",
+    }
+}
+
+/// 逐行读取密码并评估强度，空行跳过
+pub fn process_pass_check(input: &str, user_inputs: &[String]) -> Result<Vec<StrengthReport>> {
+    let mut reader = get_reader(input)?;
+    let mut buf = String::new();
+    reader.read_to_string(&mut buf)?;
+
+    let user_inputs = user_inputs.iter().map(|s| s.as_str()).collect::<Vec<_>>();
+    let reports = buf
+        .lines()
+        .enumerate()
+        .filter(|(_, pwd)| !pwd.is_empty())
+        .map(|(i, pwd)| check_strength(i + 1, pwd, &user_inputs))
+        .collect();
+    Ok(reports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_strength() {
+        let weak = check_strength(1, "password123", &[]);
+        assert!(weak.score <= 1);
+        assert!(weak.patterns.iter().any(|p| p.pattern == "dictionary"));
+        assert!(weak.warning.is_some() || !weak.suggestions.is_empty());
+
+        let strong = check_strength(2, "uRmihL^2YrGU$Y7q", &[]);
+        assert_eq!(strong.score, 4);
+        assert_eq!(strong.patterns.iter().map(|p| p.length).sum::<usize>(), 16);
+        let json = serde_json::to_string(&strong).unwrap();
+        assert!(!json.contains("uRmihL"));
+
+        // 用户相关的词会被当成字典词
+        let with_user = check_strength(3, "yxnne2024", &["yxnne"]);
+        let without_user = check_strength(3, "yxnne2024", &[]);
+        assert!(with_user.guesses_log10 < without_user.guesses_log10);
+    }
+}
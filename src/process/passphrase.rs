@@ -0,0 +1,108 @@
+use std::{collections::HashSet, fs};
+
+use anyhow::{anyhow, Result};
+use rand::{seq::IndexedRandom, Rng, RngCore};
+
+use super::gen_pass::entropy_bits;
+
+// 编译时把 EFF 大词表嵌入二进制，不依赖运行时文件
+const WORDLIST: &str = include_str!("../../assets/eff_large_wordlist.txt");
+
+/// 读取词表，每行取最后一列，兼容 EFF 的 "11111\tabacus" 格式，重复的词只保留一个
+pub fn load_wordlist(path: Option<&str>) -> Result<Vec<String>> {
+    let content = match path {
+        Some(path) => fs::read_to_string(path)?,
+        None => WORDLIST.to_string(),
+    };
+
+    let mut seen = HashSet::new();
+    let words = content
+        .lines()
+        .filter_map(|line| line.split_whitespace().last())
+        .filter(|word| seen.insert(*word))
+        .map(|word| word.to_string())
+        .collect::<Vec<_>>();
+
+    if words.len() < 2 {
+        return Err(anyhow!("wordlist needs at least 2 distinct words"));
+    }
+    Ok(words)
+}
+
+/// 生成 diceware 风格的密码短语，返回短语和熵（bit）
+pub fn process_passphrase(
+    wordlist: &[String],
+    words: u8,
+    separator: &str,
+    capitalize: bool,
+    add_digit: bool,
+    rng: &mut dyn RngCore,
+) -> Result<(String, f64)> {
+    if words == 0 {
+        return Err(anyhow!("passphrase needs at least 1 word"));
+    }
+
+    let mut chosen = Vec::with_capacity(words as usize);
+    for _ in 0..words {
+        let word = wordlist.choose(rng).expect("wordlist won't be empty");
+        let word = if capitalize {
+            capitalize_word(word)
+        } else {
+            word.clone()
+        };
+        chosen.push(word);
+    }
+
+    // 每个词从词表里独立选择: words * log2(词表大小)
+    let mut entropy = entropy_bits(wordlist.len(), words as usize);
+    if add_digit {
+        // 在随机一个词后面加一位数字: 数字 10 种 * 位置 words 种
+        let pos = rng.random_range(0..chosen.len());
+        chosen[pos].push(char::from(b'0' + rng.random_range(0..10u8)));
+        entropy += entropy_bits(10, 1) + entropy_bits(words as usize, 1);
+    }
+
+    Ok((chosen.join(separator), entropy))
+}
+
+fn capitalize_word(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_passphrase() -> Result<()> {
+        let wordlist = load_wordlist(None)?;
+
+        let (pass, entropy) = process_passphrase(&wordlist, 6, "-", true, false, &mut rand::rng())?;
+        let parts = pass.split('-').collect::<Vec<_>>();
+        assert_eq!(parts.len(), 6);
+        assert!(parts.iter().all(|w| w.starts_with(char::is_uppercase)));
+        assert_eq!(entropy, 6.0 * (wordlist.len() as f64).log2());
+
+        let (pass, _) = process_passphrase(&wordlist, 4, " ", false, true, &mut rand::rng())?;
+        assert_eq!(pass.chars().filter(|c| c.is_ascii_digit()).count(), 1);
+
+        assert!(process_passphrase(&wordlist, 0, "-", false, false, &mut rand::rng()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_eff_wordlist() -> Result<()> {
+        // 内置的 EFF 大词表: 5 位骰子编号 + tab + 单词，7776 个词每个约 12.9 bit
+        let wordlist = load_wordlist(None)?;
+        assert_eq!(wordlist.len(), 7776);
+        assert_eq!(wordlist[0], "abacus");
+        assert_eq!(wordlist[7775], "zoom");
+        let (_, entropy) = process_passphrase(&wordlist, 6, "-", false, false, &mut rand::rng())?;
+        assert!((entropy - 77.5).abs() < 0.1);
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+use anyhow::{anyhow, Result};
+use rand::{seq::IndexedRandom, Rng, RngCore};
+
+use super::gen_pass::entropy_bits;
+
+// 发音模式只用常见的辅音 / 元音，去掉了 q x 这类不好读的字母
+const CONSONANTS: &[u8] = b"bcdfghjkmnprstvwz";
+const VOWELS: &[u8] = b"aeiou";
+const UPPER_CONSONANTS: &[u8] = b"BCDFGHJKMNPRSTVWZ";
+const UPPER_VOWELS: &[u8] = b"AEU";
+const LETTERS: &[u8] = b"abcdefghijkmnopqrstuvwxyz";
+const UPPER_LETTERS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ";
+const DIGITS: &[u8] = b"0123456789";
+const SYMBOLS: &[u8] = b"!@#$%^&*_";
+
+/// 模板里每个占位符对应的字符集合，其他字符原样输出，`\` 转义下一个字符
+/// - C / c: 大写 / 小写辅音
+/// - V / v: 大写 / 小写元音
+/// - A / a: 大写 / 小写字母
+/// - 9: 数字，#: 符号
+fn placeholder(c: char) -> Option<&'static [u8]> {
+    match c {
+        'C' => Some(UPPER_CONSONANTS),
+        'c' => Some(CONSONANTS),
+        'V' => Some(UPPER_VOWELS),
+        'v' => Some(VOWELS),
+        'A' => Some(UPPER_LETTERS),
+        'a' => Some(LETTERS),
+        '9' => Some(DIGITS),
+        '#' => Some(SYMBOLS),
+        _ => None,
+    }
+}
+
+enum Token {
+    Set(&'static [u8]),
+    Literal(char),
+}
+
+fn parse_pattern(pattern: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '\\' => Token::Literal(
+                chars
+                    .next()
+                    .ok_or_else(|| anyhow!("pattern ends with a dangling \\"))?,
+            ),
+            c => placeholder(c).map_or(Token::Literal(c), Token::Set),
+        };
+        tokens.push(token);
+    }
+    if !tokens.iter().any(|t| matches!(t, Token::Set(_))) {
+        return Err(anyhow!("pattern {:?} has no random placeholder", pattern));
+    }
+    Ok(tokens)
+}
+
+/// 按模板生成密码，比如 "Cvccvc-99-Cvccvc"
+pub fn generate_pattern<R: Rng + ?Sized>(pattern: &str, rng: &mut R) -> Result<String> {
+    let password = parse_pattern(pattern)?
+        .into_iter()
+        .map(|token| match token {
+            Token::Set(set) => *set.choose(rng).expect("set won't be empty") as char,
+            Token::Literal(c) => c,
+        })
+        .collect();
+    Ok(password)
+}
+
+/// 模板的熵: 每个占位符 log2(集合大小) 之和，字面量不贡献熵
+pub fn pattern_entropy(pattern: &str) -> Result<f64> {
+    let bits = parse_pattern(pattern)?
+        .iter()
+        .map(|token| match token {
+            Token::Set(set) => entropy_bits(set.len(), 1),
+            Token::Literal(_) => 0.0,
+        })
+        .sum();
+    Ok(bits)
+}
+
+/// 发音模式: 辅音元音交替，首字母大写，比如 length 8 -> "Cvcvcvcv"
+pub fn pronounceable_pattern(length: u8) -> String {
+    (0..length)
+        .map(|i| match (i, i % 2) {
+            (0, _) => 'C',
+            (_, 0) => 'c',
+            _ => 'v',
+        })
+        .collect()
+}
+
+/// 生成密码并返回熵
+pub fn process_pattern(pattern: &str, rng: &mut dyn RngCore) -> Result<(String, f64)> {
+    let password = generate_pattern(pattern, rng)?;
+    Ok((password, pattern_entropy(pattern)?))
+}
+
+/// 按熵给出强度标签，弱的模式在输出里一眼就能看出来
+pub fn entropy_label(bits: f64) -> &'static str {
+    match bits {
+        b if b < 40.0 => "weak",
+        b if b < 60.0 => "fair",
+        b if b < 80.0 => "strong",
+        _ => "very strong",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_pattern() -> Result<()> {
+        let (pwd, bits) = process_pattern("Cvccvc-99-\\C", &mut rand::rng())?;
+        let chars = pwd.chars().collect::<Vec<_>>();
+        assert_eq!(chars.len(), 11);
+        assert!(UPPER_CONSONANTS.contains(&(chars[0] as u8)));
+        assert!(VOWELS.contains(&(chars[1] as u8)));
+        assert_eq!(chars[6], '-');
+        assert!(chars[7].is_ascii_digit() && chars[8].is_ascii_digit());
+        assert_eq!(&pwd[9..], "-C");
+
+        let expected = 4.0 * 17f64.log2() + 2.0 * 5f64.log2() + 2.0 * 10f64.log2();
+        assert!((bits - expected).abs() < 1e-9);
+
+        assert!(process_pattern("---", &mut rand::rng()).is_err());
+        assert!(process_pattern("99\\", &mut rand::rng()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pronounceable_pattern() {
+        assert_eq!(pronounceable_pattern(6), "Cvcvcv");
+        assert_eq!(entropy_label(30.0), "weak");
+        assert_eq!(entropy_label(128.0), "very strong");
+    }
+}
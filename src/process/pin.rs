@@ -0,0 +1,165 @@
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::{anyhow, Result};
+use rand::RngCore;
+
+use crate::{generate_password, GenPassConfig};
+
+// 公开泄露数据里最常见的 PIN，其中很多也会被下面的规则拦住，这里单独列出来方便报告
+const COMMON_PINS: &[&str] = &[
+    "1234", "1111", "0000", "1212", "7777", "1004", "2000", "4444", "2222", "6969", "9999", "3333",
+    "5555", "6666", "1122", "1313", "8888", "4321", "2001", "1010", "123456", "654321", "111111",
+    "000000", "123123", "666666", "121212", "112233", "789456", "159753", "147258", "123321",
+    "696969", "520520", "987654", "101010", "131313", "12345678", "87654321", "11111111",
+    "00000000", "12341234", "11223344",
+];
+
+const MIN_PIN_LENGTH: u8 = 4;
+
+/// PIN 被拒绝的原因
+pub const PIN_COMMON: &str = "common PIN";
+pub const PIN_REPEAT: &str = "repeated digits";
+pub const PIN_SEQUENCE: &str = "sequence";
+pub const PIN_DATE: &str = "birth date or year";
+
+/// 检查 PIN 是否命中弱模式，返回命中的原因，为空表示通过
+pub fn weak_pin_patterns(pin: &str) -> Vec<&'static str> {
+    let digits = pin
+        .bytes()
+        .map(|b| b.wrapping_sub(b'0') as i32)
+        .collect::<Vec<_>>();
+    let mut ret = Vec::new();
+    if COMMON_PINS.contains(&pin) {
+        ret.push(PIN_COMMON);
+    }
+    if is_repeat(&digits) {
+        ret.push(PIN_REPEAT);
+    }
+    if is_sequence(&digits) {
+        ret.push(PIN_SEQUENCE);
+    }
+    if is_date(&digits) {
+        ret.push(PIN_DATE);
+    }
+    ret
+}
+
+/// 生成数字 PIN，命中弱模式的候选会被丢弃，同时统计每种模式拒绝了多少次
+pub fn process_pin(
+    length: u8,
+    count: usize,
+    unique: bool,
+    rng: &mut dyn RngCore,
+) -> Result<(Vec<String>, BTreeMap<&'static str, usize>)> {
+    if length < MIN_PIN_LENGTH {
+        return Err(anyhow!("PIN length must be at least {}", MIN_PIN_LENGTH));
+    }
+    // 复用 genpass 的生成逻辑，只是把字符集换成数字
+    let config = GenPassConfig {
+        charset: Some("0123456789".into()),
+        ..GenPassConfig::new(length)
+    };
+
+    let mut pins = Vec::with_capacity(count);
+    let mut seen = HashSet::new();
+    let mut avoided = BTreeMap::new();
+    let mut attempts = 0;
+    while pins.len() < count {
+        if attempts >= count * 100 + 1000 {
+            return Err(anyhow!(
+                "cannot generate {} PINs of length {}, only got {}",
+                count,
+                length,
+                pins.len()
+            ));
+        }
+        attempts += 1;
+
+        let pin = generate_password(&config, rng)?;
+        let patterns = weak_pin_patterns(&pin);
+        if !patterns.is_empty() {
+            for p in patterns {
+                *avoided.entry(p).or_insert(0) += 1;
+            }
+            continue;
+        }
+        if unique && !seen.insert(pin.clone()) {
+            continue;
+        }
+        pins.push(pin);
+    }
+    Ok((pins, avoided))
+}
+
+/// 连续 3 个相同数字，或者整个 PIN 由更短的片段重复组成，比如 1212 / 123123
+fn is_repeat(digits: &[i32]) -> bool {
+    if digits.chunk_by(|a, b| a == b).any(|run| run.len() >= 3) {
+        return true;
+    }
+    let len = digits.len();
+    (1..len)
+        .filter(|unit| len.is_multiple_of(*unit))
+        .any(|unit| digits.chunks(unit).all(|c| c == &digits[..unit]))
+}
+
+/// 整个 PIN 是步长 ±1 / ±2 的等差数列（允许 9 -> 0 回绕），或者包含 4 个以上连续递增 / 递减的数字
+fn is_sequence(digits: &[i32]) -> bool {
+    let steps = digits
+        .windows(2)
+        .map(|w| (w[1] - w[0]).rem_euclid(10))
+        .collect::<Vec<_>>();
+    if [1, 2, 8, 9].iter().any(|s| steps.iter().all(|x| x == s)) {
+        return true;
+    }
+    steps
+        .chunk_by(|a, b| a == b)
+        .any(|run| (run[0] == 1 || run[0] == 9) && run.len() >= 3)
+}
+
+/// 生日和年份: 4 位的 19xx / 20xx / MMDD / DDMM，6 位的 DDMMYY / MMDDYY / YYMMDD，8 位的 DDMMYYYY / MMDDYYYY / YYYYMMDD
+fn is_date(digits: &[i32]) -> bool {
+    let num = |range: std::ops::Range<usize>| digits[range].iter().fold(0, |acc, d| acc * 10 + d);
+    let md = |m: i32, d: i32| (1..=12).contains(&m) && (1..=31).contains(&d);
+    let year = |y: i32| (1900..=2099).contains(&y);
+    match digits.len() {
+        4 => year(num(0..4)) || md(num(0..2), num(2..4)) || md(num(2..4), num(0..2)),
+        6 => md(num(2..4), num(0..2)) || md(num(0..2), num(2..4)) || md(num(2..4), num(4..6)),
+        8 => {
+            (year(num(4..8)) && (md(num(2..4), num(0..2)) || md(num(0..2), num(2..4))))
+                || (year(num(0..4)) && md(num(4..6), num(6..8)))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weak_pin_patterns() {
+        assert_eq!(weak_pin_patterns("123456"), [PIN_COMMON, PIN_SEQUENCE]);
+        assert_eq!(weak_pin_patterns("000000"), [PIN_COMMON, PIN_REPEAT]);
+        assert_eq!(weak_pin_patterns("246802"), [PIN_SEQUENCE]);
+        assert_eq!(weak_pin_patterns("905678"), [PIN_SEQUENCE]);
+        assert_eq!(weak_pin_patterns("1987"), [PIN_DATE]);
+        assert_eq!(weak_pin_patterns("180590"), [PIN_DATE]);
+        assert_eq!(weak_pin_patterns("19900518"), [PIN_DATE]);
+        assert_eq!(weak_pin_patterns("373737"), [PIN_REPEAT]);
+        assert!(weak_pin_patterns("482915").is_empty());
+        assert!(weak_pin_patterns("7396").is_empty());
+    }
+
+    #[test]
+    fn test_process_pin() -> Result<()> {
+        let (pins, _) = process_pin(6, 200, true, &mut rand::rng())?;
+        assert_eq!(pins.len(), 200);
+        for pin in &pins {
+            assert_eq!(pin.len(), 6);
+            assert!(pin.bytes().all(|b| b.is_ascii_digit()));
+            assert!(weak_pin_patterns(pin).is_empty(), "{}", pin);
+        }
+        assert!(process_pin(3, 1, false, &mut rand::rng()).is_err());
+        Ok(())
+    }
+}
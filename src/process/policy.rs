@@ -0,0 +1,181 @@
+use std::{fs, io::Read, path::Path};
+
+use anyhow::{anyhow, Result};
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::{generate_password, get_reader, GenPassConfig};
+
+// 随机生成的密码不满足禁止子串 / 连续重复时重新生成，正常配置下很少需要重试
+const MAX_ATTEMPTS: usize = 1000;
+
+/// 密码策略文件，比如 policies/aws.yaml
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub max_length: usize,
+    #[serde(default)]
+    pub required: Vec<CharKind>,
+    #[serde(default)]
+    pub forbidden_chars: String,
+    #[serde(default)]
+    pub forbidden_substrings: Vec<String>,
+    /// 同一个字符最多连续出现的次数
+    pub max_repeat: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CharKind {
+    Uppercase,
+    Lowercase,
+    Number,
+    Symbol,
+}
+
+impl CharKind {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            CharKind::Uppercase => c.is_uppercase(),
+            CharKind::Lowercase => c.is_lowercase(),
+            CharKind::Number => c.is_ascii_digit(),
+            CharKind::Symbol => !c.is_alphanumeric(),
+        }
+    }
+}
+
+impl PasswordPolicy {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let policy: Self = serde_yaml::from_str(&content)?;
+        if policy.min_length == 0 || policy.min_length > policy.max_length {
+            return Err(anyhow!(
+                "invalid length range {}..={}",
+                policy.min_length,
+                policy.max_length
+            ));
+        }
+        Ok(policy)
+    }
+
+    /// 把策略合并到 genpass 配置上: 长度限制在范围内，必需的字符类至少一个，禁止字符加入排除列表
+    pub fn apply(&self, config: &mut GenPassConfig) -> Result<()> {
+        let length = (config.length as usize).clamp(self.min_length, self.max_length);
+        config.length = u8::try_from(length)
+            .map_err(|_| anyhow!("policy length {} exceeds {}", length, u8::MAX))?;
+        config.exclude.push_str(&self.forbidden_chars);
+
+        for kind in &self.required {
+            let (enabled, min) = match kind {
+                CharKind::Uppercase => (&mut config.upper, &mut config.min_upper),
+                CharKind::Lowercase => (&mut config.lower, &mut config.min_lower),
+                CharKind::Number => (&mut config.number, &mut config.min_digits),
+                CharKind::Symbol => (&mut config.symbol, &mut config.min_symbols),
+            };
+            if !*enabled {
+                return Err(anyhow!("policy requires {:?} but it is disabled", kind));
+            }
+            *min = Some(min.unwrap_or(1).max(1));
+        }
+        Ok(())
+    }
+
+    /// 返回不满足策略的原因，为空表示通过
+    pub fn violations(&self, password: &str) -> Vec<String> {
+        let mut ret = Vec::new();
+        let len = password.chars().count();
+        if len < self.min_length || len > self.max_length {
+            ret.push(format!(
+                "length {} not in {}..={}",
+                len, self.min_length, self.max_length
+            ));
+        }
+        for kind in &self.required {
+            if !password.chars().any(|c| kind.matches(c)) {
+                ret.push(format!("missing {:?}", kind));
+            }
+        }
+        let forbidden = password
+            .chars()
+            .filter(|c| self.forbidden_chars.contains(*c))
+            .collect::<String>();
+        if !forbidden.is_empty() {
+            ret.push(format!("forbidden characters {:?}", forbidden));
+        }
+        let lower = password.to_lowercase();
+        for sub in &self.forbidden_substrings {
+            if lower.contains(&sub.to_lowercase()) {
+                ret.push(format!("contains forbidden substring {:?}", sub));
+            }
+        }
+        if let Some(max) = self.max_repeat {
+            let repeat = longest_repeat(password);
+            if repeat > max {
+                ret.push(format!("{} repeated characters, max {}", repeat, max));
+            }
+        }
+        ret
+    }
+}
+
+fn longest_repeat(s: &str) -> usize {
+    let chars = s.chars().collect::<Vec<_>>();
+    chars
+        .chunk_by(|a, b| a == b)
+        .map(|run| run.len())
+        .max()
+        .unwrap_or(0)
+}
+
+/// 按策略生成密码，不满足的结果会被丢弃重新生成
+pub fn process_genpass_policy(config: &GenPassConfig, policy: &PasswordPolicy) -> Result<String> {
+    generate_password_policy(config, policy, &mut rand::rng())
+}
+
+pub fn generate_password_policy<R: Rng + ?Sized>(
+    config: &GenPassConfig,
+    policy: &PasswordPolicy,
+    rng: &mut R,
+) -> Result<String> {
+    let mut config = config.clone();
+    policy.apply(&mut config)?;
+    for _ in 0..MAX_ATTEMPTS {
+        let pwd = generate_password(&config, rng)?;
+        if policy.violations(&pwd).is_empty() {
+            return Ok(pwd);
+        }
+    }
+    Err(anyhow!(
+        "cannot generate a password satisfying the policy after {} attempts",
+        MAX_ATTEMPTS
+    ))
+}
+
+/// 逐行检查已有的密码，返回每一行的检查结果
+pub fn process_policy_check(input: &str, policy: &PasswordPolicy) -> Result<Vec<Vec<String>>> {
+    let mut reader = get_reader(input)?;
+    let mut buf = String::new();
+    reader.read_to_string(&mut buf)?;
+    Ok(buf.lines().map(|pwd| policy.violations(pwd)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_generate_and_check() -> Result<()> {
+        let policy = PasswordPolicy::load("policies/aws.yaml")?;
+        for _ in 0..20 {
+            let pwd = process_genpass_policy(&GenPassConfig::new(16), &policy)?;
+            assert!(policy.violations(&pwd).is_empty(), "{}", pwd);
+        }
+
+        assert!(policy.violations("Abcdefgh1234!xyz").is_empty());
+        assert_eq!(policy.violations("short").len(), 4);
+        assert_eq!(policy.violations("Aaaaa1234!xyzxyz").len(), 1);
+        assert_eq!(policy.violations("MyPassword1234!x").len(), 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,272 @@
+use std::{collections::HashSet, io::Read};
+
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use data_encoding::{HEXLOWER, HEXLOWER_PERMISSIVE};
+use rand::Rng;
+
+use crate::{get_reader, ShareFormat};
+
+/// 一个 share: x 坐标（1..=255）、恢复需要的份数，以及 secret 每个字节对应多项式在 x 处的取值
+#[derive(Debug, Clone, PartialEq)]
+pub struct Share {
+    pub index: u8,
+    pub threshold: u8,
+    pub data: Vec<u8>,
+}
+
+impl Share {
+    /// 文本格式: <index>/<threshold>:<base64|hex>:<data>:<crc32>，比如 1/3:base64:q83v:1a2b3c4d
+    pub fn encode(&self, format: ShareFormat) -> String {
+        let data = match format {
+            ShareFormat::Base64 => STANDARD.encode(&self.data),
+            ShareFormat::Hex => HEXLOWER.encode(&self.data),
+        };
+        format!(
+            "{}/{}:{}:{}:{:08x}",
+            self.index,
+            self.threshold,
+            format,
+            data,
+            self.checksum()
+        )
+    }
+
+    pub fn parse(s: &str) -> Result<Self> {
+        let parts = s.trim().split(':').collect::<Vec<_>>();
+        let [header, format, data, checksum] = parts[..] else {
+            return Err(anyhow!(
+                "invalid share, expect index/threshold:format:data:checksum"
+            ));
+        };
+        let (index, threshold) = header
+            .split_once('/')
+            .ok_or_else(|| anyhow!("invalid share header {:?}", header))?;
+        let data = match format.parse::<ShareFormat>()? {
+            ShareFormat::Base64 => STANDARD.decode(data)?,
+            // 手改过的 share 可能带非 ASCII 字符，按字节切片会 panic，交给 data_encoding 报错
+            ShareFormat::Hex => HEXLOWER_PERMISSIVE.decode(data.as_bytes())?,
+        };
+        let share = Share {
+            index: index.parse()?,
+            threshold: threshold.parse()?,
+            data,
+        };
+        if share.index == 0 {
+            return Err(anyhow!("share index must not be 0"));
+        }
+        if format!("{:08x}", share.checksum()) != checksum {
+            return Err(anyhow!("share {} checksum mismatch", share.index));
+        }
+        Ok(share)
+    }
+
+    // 校验和覆盖 index 和 threshold，改错编号也能发现
+    fn checksum(&self) -> u32 {
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(&[self.index, self.threshold]);
+        hasher.update(&self.data);
+        hasher.finalize()
+    }
+}
+
+/// 把 secret 拆成 shares 份，任意 threshold 份可以恢复，少于 threshold 份得不到任何信息
+pub fn split_secret<R: Rng + ?Sized>(
+    secret: &[u8],
+    shares: u8,
+    threshold: u8,
+    rng: &mut R,
+) -> Result<Vec<Share>> {
+    if secret.is_empty() {
+        return Err(anyhow!("secret must not be empty"));
+    }
+    if threshold < 2 || threshold > shares {
+        return Err(anyhow!(
+            "threshold must be between 2 and shares ({}), got {}",
+            shares,
+            threshold
+        ));
+    }
+
+    let mut ret = (1..=shares)
+        .map(|index| Share {
+            index,
+            threshold,
+            data: Vec::with_capacity(secret.len()),
+        })
+        .collect::<Vec<_>>();
+    // 每个字节一个 threshold - 1 次的随机多项式，常数项就是这个字节
+    let mut coefficients = vec![0u8; threshold as usize];
+    for byte in secret {
+        coefficients[0] = *byte;
+        rng.fill(&mut coefficients[1..]);
+        for share in ret.iter_mut() {
+            share.data.push(eval(&coefficients, share.index));
+        }
+    }
+    Ok(ret)
+}
+
+/// 拉格朗日插值求多项式在 x = 0 处的值，得到原来的 secret
+pub fn combine_shares(shares: &[Share]) -> Result<Vec<u8>> {
+    let first = shares.first().ok_or_else(|| anyhow!("no shares given"))?;
+    let threshold = first.threshold as usize;
+    let mut seen = HashSet::new();
+    let shares = shares
+        .iter()
+        .filter(|s| seen.insert(s.index))
+        .collect::<Vec<_>>();
+    if shares.len() < threshold {
+        return Err(anyhow!(
+            "need {} distinct shares, only got {}",
+            threshold,
+            shares.len()
+        ));
+    }
+    if shares
+        .iter()
+        .any(|s| s.threshold != first.threshold || s.data.len() != first.data.len())
+    {
+        return Err(anyhow!("shares come from different secrets"));
+    }
+
+    // 只用前 threshold 份，多余的 share 不影响结果
+    let shares = &shares[..threshold];
+    let weights = shares
+        .iter()
+        .map(|si| {
+            // l_i(0) = prod(x_j / (x_j - x_i))，GF(256) 里减法就是异或
+            shares
+                .iter()
+                .filter(|sj| sj.index != si.index)
+                .fold(1u8, |acc, sj| {
+                    gf_mul(acc, gf_div(sj.index, sj.index ^ si.index))
+                })
+        })
+        .collect::<Vec<_>>();
+    let secret = (0..first.data.len())
+        .map(|i| {
+            shares
+                .iter()
+                .zip(&weights)
+                .fold(0u8, |acc, (s, w)| acc ^ gf_mul(s.data[i], *w))
+        })
+        .collect();
+    Ok(secret)
+}
+
+pub fn process_secret_split(
+    input: &str,
+    shares: u8,
+    threshold: u8,
+    format: ShareFormat,
+) -> Result<Vec<String>> {
+    let mut reader = get_reader(input)?;
+    let mut secret = Vec::new();
+    reader.read_to_end(&mut secret)?;
+    let shares = split_secret(&secret, shares, threshold, &mut rand::rng())?;
+    Ok(shares.iter().map(|s| s.encode(format)).collect())
+}
+
+/// 从多个文件里读取 share，每行一个，空行忽略
+pub fn process_secret_combine(inputs: &[String]) -> Result<Vec<u8>> {
+    let mut shares = Vec::new();
+    for input in inputs {
+        let mut reader = get_reader(input)?;
+        let mut buf = String::new();
+        reader.read_to_string(&mut buf)?;
+        for line in buf.lines().filter(|l| !l.trim().is_empty()) {
+            shares.push(Share::parse(line)?);
+        }
+    }
+    combine_shares(&shares)
+}
+
+/// 秦九韶算法求多项式在 x 处的值
+fn eval(coefficients: &[u8], x: u8) -> u8 {
+    coefficients
+        .iter()
+        .rev()
+        .fold(0u8, |acc, c| gf_mul(acc, x) ^ c)
+}
+
+/// GF(256) 乘法，和 AES 一样使用不可约多项式 x^8 + x^4 + x^3 + x + 1
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut ret = 0u8;
+    while b != 0 {
+        if b & 1 != 0 {
+            ret ^= a;
+        }
+        let carry = a & 0x80 != 0;
+        a <<= 1;
+        if carry {
+            a ^= 0x1B;
+        }
+        b >>= 1;
+    }
+    ret
+}
+
+/// a / b = a * b^254，乘法群的阶是 255
+fn gf_div(a: u8, b: u8) -> u8 {
+    let mut inv = 1u8;
+    for _ in 0..254 {
+        inv = gf_mul(inv, b);
+    }
+    gf_mul(a, inv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gf256() {
+        // FIPS-197 里的例子
+        assert_eq!(gf_mul(0x57, 0x83), 0xC1);
+        assert_eq!(gf_mul(0x53, 0xCA), 0x01);
+        assert_eq!(gf_div(0x01, 0x53), 0xCA);
+    }
+
+    #[test]
+    fn test_split_combine() -> Result<()> {
+        let secret = std::fs::read("fixtures/ed25519.sk")?;
+        let shares = split_secret(&secret, 5, 3, &mut rand::rng())?;
+        assert_eq!(shares.len(), 5);
+
+        // 任意 3 份都能恢复
+        for a in 0..5 {
+            for b in a + 1..5 {
+                for c in b + 1..5 {
+                    let picked = [shares[a].clone(), shares[b].clone(), shares[c].clone()];
+                    assert_eq!(combine_shares(&picked)?, secret);
+                }
+            }
+        }
+        assert!(combine_shares(&shares[..2]).is_err());
+        assert!(split_secret(&secret, 3, 4, &mut rand::rng()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_share_encoding() -> Result<()> {
+        let shares = split_secret(b"hunter2", 3, 2, &mut rand::rng())?;
+        for format in [ShareFormat::Base64, ShareFormat::Hex] {
+            let text = shares[1].encode(format);
+            assert!(text.starts_with(&format!("2/2:{}:", format)));
+            assert_eq!(Share::parse(&text)?, shares[1]);
+        }
+
+        // 改掉编号或数据都会被校验和发现
+        let text = shares[0].encode(ShareFormat::Hex);
+        assert!(Share::parse(&text.replacen("1/2", "3/2", 1)).is_err());
+        let mut tampered = text.clone().into_bytes();
+        tampered[8] = if tampered[8] == b'0' { b'1' } else { b'0' };
+        assert!(Share::parse(&String::from_utf8(tampered)?).is_err());
+
+        // 非 ASCII 或奇数长度的 hex 返回错误而不是 panic
+        assert!(Share::parse("1/2:hex:é0:00000000").is_err());
+        assert!(Share::parse("1/2:hex:abc:00000000").is_err());
+        Ok(())
+    }
+}
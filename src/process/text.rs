@@ -1,10 +1,19 @@
 use std::{fs, io::Read, path::Path};
 
-use anyhow::Result;
-use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use anyhow::{anyhow, Result};
+use base64::{
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+    Engine as _,
+};
+use data_encoding::{HEXLOWER, HEXLOWER_PERMISSIVE};
 use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use rand::{Rng, RngCore};
 
-use crate::{get_reader, process_genpass, TextSignFormat};
+use crate::{get_reader, KeyEncoding, TextSignFormat};
+
+// 带版本号的 black3 key 文件: "black3:v1:<raw|hex|base64>:" 后面跟 key 本身
+// 没有这个前缀的按旧格式处理（取前 32 个字节），fixtures/black3.txt 这样的 key 依然可以使用
+const BLACK3_HEADER: &str = "black3:v1:";
 
 pub trait KeyLoader {
     fn load(path: impl AsRef<Path>) -> Result<Self>
@@ -16,7 +25,8 @@ pub trait KeyGenerator {
     // 一对 key 和一个key 统一
     // ed25519 生成一对key
     // black3 生成一个key
-    fn generate() -> Result<Vec<Vec<u8>>>;
+    // rng 由调用方传入，测试和演示时可以用固定 seed 得到相同的 key
+    fn generate(rng: &mut dyn RngCore) -> Result<Vec<Vec<u8>>>;
 }
 
 pub trait TextSign {
@@ -46,17 +56,52 @@ impl Black3 {
     }
 
     pub fn try_new(key: &[u8]) -> Result<Self> {
-        let key = &key[..32];
+        let key = key
+            .get(..32)
+            .ok_or_else(|| anyhow!("black3 key must be at least 32 bytes"))?;
         let key = key.try_into()?;
         let signer = Black3::new(key);
         Ok(signer)
     }
+
+    /// 解析 key 文件的内容，兼容没有头部的旧格式
+    pub fn decode(content: &[u8]) -> Result<Self> {
+        let Some(rest) = content.strip_prefix(BLACK3_HEADER.as_bytes()) else {
+            return Self::try_new(content);
+        };
+        let pos = rest
+            .iter()
+            .position(|b| *b == b':')
+            .ok_or_else(|| anyhow!("invalid black3 key header"))?;
+        let encoding = std::str::from_utf8(&rest[..pos])?.parse::<KeyEncoding>()?;
+        let data = &rest[pos + 1..];
+        let key = match encoding {
+            KeyEncoding::Raw => data.to_vec(),
+            KeyEncoding::Hex => HEXLOWER_PERMISSIVE.decode(data.trim_ascii())?,
+            KeyEncoding::Base64 => STANDARD.decode(data.trim_ascii())?,
+        };
+        let key = key
+            .try_into()
+            .map_err(|_| anyhow!("black3 key must be exactly 32 bytes"))?;
+        Ok(Self::new(key))
+    }
+
+    /// 按指定编码写出带头部的 key，hex 和 base64 末尾带换行方便在终端查看
+    pub fn encode(&self, encoding: KeyEncoding) -> Vec<u8> {
+        let mut ret = format!("{}{}:", BLACK3_HEADER, encoding).into_bytes();
+        match encoding {
+            KeyEncoding::Raw => ret.extend_from_slice(&self.key),
+            KeyEncoding::Hex => ret.extend(format!("{}\n", HEXLOWER.encode(&self.key)).bytes()),
+            KeyEncoding::Base64 => ret.extend(format!("{}\n", STANDARD.encode(self.key)).bytes()),
+        }
+        ret
+    }
 }
 
 impl KeyLoader for Black3 {
     fn load(path: impl AsRef<Path>) -> Result<Self> {
         let key = fs::read(path)?;
-        Self::try_new(&key)
+        Self::decode(&key)
     }
 }
 
@@ -68,16 +113,16 @@ impl KeyLoader for Ed25519Signer {
 }
 
 impl KeyGenerator for Black3 {
-    fn generate() -> Result<Vec<Vec<u8>>> {
-        let key = process_genpass(32, true, true, true, true)?;
-        let key = key.as_bytes().into();
-        Ok(vec![key])
+    fn generate(rng: &mut dyn RngCore) -> Result<Vec<Vec<u8>>> {
+        // 32 个均匀分布的随机字节，完整的 256 bit
+        let key: [u8; 32] = rng.random();
+        Ok(vec![key.to_vec()])
     }
 }
 
 impl KeyGenerator for Ed25519Signer {
-    fn generate() -> Result<Vec<Vec<u8>>> {
-        let secret: [u8; 32] = rand::random();
+    fn generate(rng: &mut dyn RngCore) -> Result<Vec<Vec<u8>>> {
+        let secret: [u8; 32] = rng.random();
         let sk = SigningKey::from_bytes(&secret);
         let pk = sk.verifying_key().to_bytes().to_vec();
         let sk = sk.to_bytes().to_vec();
@@ -210,13 +255,26 @@ pub fn process_text_verify(
     Ok(verify)
 }
 
-pub fn process_text_generate_keye(format: TextSignFormat) -> Result<Vec<Vec<u8>>> {
+/// 生成 key，black3 按 encoding 编码之后再写入文件，ed25519 保持原始字节
+pub fn process_text_generate_keye(
+    format: TextSignFormat,
+    encoding: KeyEncoding,
+    rng: &mut dyn RngCore,
+) -> Result<Vec<Vec<u8>>> {
     match format {
-        TextSignFormat::Black3 => Black3::generate(),
-        TextSignFormat::Ed25519 => Ed25519Signer::generate(),
+        TextSignFormat::Black3 => {
+            let key = Black3::generate(rng)?;
+            Ok(vec![Black3::try_new(&key[0])?.encode(encoding)])
+        }
+        TextSignFormat::Ed25519 => Ed25519Signer::generate(rng),
     }
 }
 
+/// 把旧格式的 black3 key 重新编码成带版本号的格式，key 本身不变
+pub fn process_text_migrate_key(key: &str, encoding: KeyEncoding) -> Result<Vec<u8>> {
+    Ok(Black3::load(key)?.encode(encoding))
+}
+
 #[cfg(test)]
 mod test {
     use super::*; // 移除未使用的导入
@@ -243,4 +301,52 @@ mod test {
         assert!(verify);
         Ok(())
     }
+
+    #[test]
+    fn test_ed25519_generate_seeded() -> Result<()> {
+        let keys = Ed25519Signer::generate(&mut crate::build_rng(Some(9)))?;
+        let pk = keys[1]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>();
+        assert_eq!(
+            pk,
+            "253b48ca9be262e28dc67002b3cf5e1e436649cfecc3327c21ae1449191e7db8"
+        );
+
+        let sk = Ed25519Signer::try_new(&keys[0])?;
+        let pk = Ed25519Verifier::try_new(&keys[1])?;
+        let signature = sk.sign(&mut b"hello".as_slice())?;
+        assert!(pk.verify(b"hello".as_slice(), &signature)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_black3_key_encoding() -> Result<()> {
+        let legacy = Black3::load("fixtures/black3.txt")?;
+        let data = b"hello world";
+        let signature = legacy.sign(&mut data.as_slice())?;
+
+        // 迁移之后还是同一个 key，旧签名依然有效
+        for encoding in [KeyEncoding::Raw, KeyEncoding::Hex, KeyEncoding::Base64] {
+            let encoded = legacy.encode(encoding);
+            assert!(encoded.starts_with(format!("black3:v1:{}:", encoding).as_bytes()));
+            let key = Black3::decode(&encoded)?;
+            assert_eq!(key.key, legacy.key);
+            assert!(key.verify(data.as_slice(), &signature)?);
+        }
+
+        let keys = process_text_generate_keye(
+            TextSignFormat::Black3,
+            KeyEncoding::Hex,
+            &mut crate::build_rng(Some(1)),
+        )?;
+        let key = Black3::decode(&keys[0])?;
+        // 不再局限于可打印字符
+        assert!(key.key.iter().any(|b| !b.is_ascii_graphic()));
+
+        assert!(Black3::decode(b"black3:v1:hex:abcd").is_err());
+        assert!(Black3::decode(b"too short").is_err());
+        Ok(())
+    }
 }
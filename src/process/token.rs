@@ -0,0 +1,113 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use rand::{seq::IndexedRandom, Rng};
+use uuid::Builder;
+
+use crate::TokenFormat;
+
+// ulid 使用 Crockford base32，去掉了 I L O U
+const CROCKFORD: &[u8] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+// nanoid 默认字母表，64 个字符
+const NANOID: &[u8] = b"_-0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+pub fn process_token(format: TokenFormat, bytes: usize, length: usize) -> Result<String> {
+    generate_token(format, bytes, length, &mut rand::rng())
+}
+
+/// 生成各种格式的随机 token，和 genpass 使用同一个 rng
+pub fn generate_token<R: Rng + ?Sized>(
+    format: TokenFormat,
+    bytes: usize,
+    length: usize,
+    rng: &mut R,
+) -> Result<String> {
+    let token = match format {
+        TokenFormat::Hex => random_bytes(bytes, rng)?
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect(),
+        TokenFormat::B64Url => URL_SAFE_NO_PAD.encode(random_bytes(bytes, rng)?),
+        TokenFormat::Uuid4 => Builder::from_random_bytes(rng.random())
+            .into_uuid()
+            .to_string(),
+        TokenFormat::Uuid7 => Builder::from_unix_timestamp_millis(now_millis()?, &rng.random())
+            .into_uuid()
+            .to_string(),
+        TokenFormat::Ulid => ulid(now_millis()?, rng.random()),
+        TokenFormat::Nanoid => {
+            if length == 0 {
+                return Err(anyhow!("nanoid length must be greater than 0"));
+            }
+            // 64 个字符，每个字符正好 6 bit，choose 保证均匀分布
+            (0..length)
+                .map(|_| *NANOID.choose(rng).expect("alphabet won't be empty") as char)
+                .collect()
+        }
+    };
+    Ok(token)
+}
+
+fn random_bytes<R: Rng + ?Sized>(bytes: usize, rng: &mut R) -> Result<Vec<u8>> {
+    if bytes == 0 {
+        return Err(anyhow!("bytes must be greater than 0"));
+    }
+    let mut buf = vec![0u8; bytes];
+    rng.fill(buf.as_mut_slice());
+    Ok(buf)
+}
+
+fn now_millis() -> Result<u64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64)
+}
+
+/// ulid: 48 bit 毫秒时间戳 + 80 bit 随机数，共 128 bit，编码成 26 个 Crockford base32 字符
+fn ulid(millis: u64, random: [u8; 10]) -> String {
+    let mut value = (millis as u128 & 0xFFFF_FFFF_FFFF) << 80;
+    value |= random.iter().fold(0u128, |acc, b| (acc << 8) | *b as u128);
+
+    // 26 * 5 = 130 bit，最高位的 2 bit 总是 0
+    (0..26)
+        .rev()
+        .map(|i| CROCKFORD[((value >> (i * 5)) & 0x1F) as usize] as char)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_token() -> Result<()> {
+        let hex = process_token(TokenFormat::Hex, 16, 0)?;
+        assert_eq!(hex.len(), 32);
+        assert!(hex.chars().all(|c| c.is_ascii_hexdigit()));
+
+        assert_eq!(process_token(TokenFormat::B64Url, 32, 0)?.len(), 43);
+
+        let uuid4 = process_token(TokenFormat::Uuid4, 0, 0)?;
+        assert_eq!(uuid::Uuid::parse_str(&uuid4)?.get_version_num(), 4);
+        let uuid7 = process_token(TokenFormat::Uuid7, 0, 0)?;
+        assert_eq!(uuid::Uuid::parse_str(&uuid7)?.get_version_num(), 7);
+
+        assert_eq!(process_token(TokenFormat::Nanoid, 0, 21)?.len(), 21);
+        assert!(process_token(TokenFormat::Hex, 0, 0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_generate_token_seeded() -> Result<()> {
+        let token = generate_token(TokenFormat::Hex, 8, 0, &mut crate::build_rng(Some(42)))?;
+        assert_eq!(token, "7848b5d711bc9883");
+        Ok(())
+    }
+
+    #[test]
+    fn test_ulid() {
+        // https://github.com/ulid/spec 里的例子使用的时间戳
+        let id = ulid(1469918176385, [0; 10]);
+        assert_eq!(id, "01ARYZ6S410000000000000000");
+        assert_eq!(ulid(0, [0xFF; 10]), "0000000000ZZZZZZZZZZZZZZZZ");
+    }
+}
@@ -0,0 +1,283 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+use chrono::{SecondsFormat, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+const VAULT_VERSION: u32 = 1;
+// 和 genpass derive 一样使用 OWASP 推荐的最低 Argon2id 参数，参数写在文件里，以后调高也能打开旧文件
+const MEMORY_KIB: u32 = 19 * 1024;
+const ITERATIONS: u32 = 2;
+const PARALLELISM: u32 = 1;
+
+/// 磁盘上的 vault 文件: 明文的 kdf 参数 + 加密后的条目
+#[derive(Debug, Serialize, Deserialize)]
+struct VaultFile {
+    version: u32,
+    kdf: KdfParams,
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KdfParams {
+    algorithm: String,
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+    salt: String,
+}
+
+/// vault 里的一条记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultEntry {
+    pub password: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    pub updated_at: String,
+}
+
+impl VaultEntry {
+    pub fn new(
+        password: String,
+        username: Option<String>,
+        url: Option<String>,
+        notes: Option<String>,
+    ) -> Self {
+        Self {
+            password,
+            username,
+            url,
+            notes,
+            updated_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        }
+    }
+}
+
+/// 解密之后的 vault，修改完需要调用 save 写回文件
+pub struct Vault {
+    path: PathBuf,
+    kdf: KdfParams,
+    key: [u8; 32],
+    entries: BTreeMap<String, VaultEntry>,
+}
+
+impl Vault {
+    /// 创建新的空 vault，文件已存在时报错，避免覆盖已有的密码
+    pub fn init(path: impl AsRef<Path>, master: &str) -> Result<Self> {
+        let path = path.as_ref();
+        if path.exists() {
+            return Err(anyhow!("vault {} already exists", path.display()));
+        }
+        let salt: [u8; 16] = rand::rng().random();
+        let kdf = KdfParams {
+            algorithm: "argon2id".into(),
+            memory_kib: MEMORY_KIB,
+            iterations: ITERATIONS,
+            parallelism: PARALLELISM,
+            salt: STANDARD.encode(salt),
+        };
+        let key = derive_key(master, &kdf)?;
+        let vault = Self {
+            path: path.into(),
+            kdf,
+            key,
+            entries: BTreeMap::new(),
+        };
+        vault.save()?;
+        Ok(vault)
+    }
+
+    pub fn open(path: impl AsRef<Path>, master: &str) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("cannot read vault {}: {}", path.display(), e))?;
+        let file: VaultFile = serde_json::from_str(&content)?;
+        if file.version != VAULT_VERSION {
+            return Err(anyhow!("unsupported vault version {}", file.version));
+        }
+
+        let key = derive_key(master, &file.kdf)?;
+        let nonce: [u8; 24] = STANDARD
+            .decode(&file.nonce)?
+            .try_into()
+            .map_err(|_| anyhow!("invalid vault nonce"))?;
+        let ciphertext = STANDARD.decode(&file.ciphertext)?;
+        let plaintext = XChaCha20Poly1305::new(&key.into())
+            .decrypt(
+                &XNonce::from(nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: &associated_data(&file.kdf)?,
+                },
+            )
+            // 认证失败时分不清是密码错了还是文件被改过
+            .map_err(|_| anyhow!("wrong master password or corrupted vault"))?;
+        let entries = serde_json::from_slice(&plaintext)?;
+        Ok(Self {
+            path: path.into(),
+            kdf: file.kdf,
+            key,
+            entries,
+        })
+    }
+
+    /// 每次保存都用新的随机 nonce 重新加密，先写临时文件再 rename，中途失败不会损坏原文件
+    /// write_private 在 rename 之前 sync_all，避免断电后留下空的或者只写了一半的 vault
+    pub fn save(&self) -> Result<()> {
+        let nonce: [u8; 24] = rand::rng().random();
+        let plaintext = serde_json::to_vec(&self.entries)?;
+        let ciphertext = XChaCha20Poly1305::new(&self.key.into())
+            .encrypt(
+                &XNonce::from(nonce),
+                Payload {
+                    msg: &plaintext,
+                    aad: &associated_data(&self.kdf)?,
+                },
+            )
+            .map_err(|_| anyhow!("failed to encrypt vault"))?;
+        let file = VaultFile {
+            version: VAULT_VERSION,
+            kdf: self.kdf.clone(),
+            nonce: STANDARD.encode(nonce),
+            ciphertext: STANDARD.encode(ciphertext),
+        };
+
+        // 在原文件名后追加 .tmp，不能用 with_extension，否则 x.tmp 的临时文件就是它自己
+        let tmp = PathBuf::from(format!("{}.tmp", self.path.display()));
+        write_private(&tmp, serde_json::to_string_pretty(&file)?.as_bytes())?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    pub fn add(&mut self, name: &str, entry: VaultEntry, overwrite: bool) -> Result<()> {
+        if !overwrite && self.entries.contains_key(name) {
+            return Err(anyhow!(
+                "entry {:?} already exists, use --force to replace",
+                name
+            ));
+        }
+        self.entries.insert(name.into(), entry);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Result<&VaultEntry> {
+        self.entries
+            .get(name)
+            .ok_or_else(|| anyhow!("entry {:?} not found", name))
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<VaultEntry> {
+        self.entries
+            .remove(name)
+            .ok_or_else(|| anyhow!("entry {:?} not found", name))
+    }
+
+    pub fn list(&self) -> impl Iterator<Item = (&String, &VaultEntry)> {
+        self.entries.iter()
+    }
+
+    /// 导出所有条目的明文 JSON，用于备份或迁移到其他密码管理器
+    pub fn export_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&self.entries)?)
+    }
+}
+
+fn derive_key(master: &str, kdf: &KdfParams) -> Result<[u8; 32]> {
+    if kdf.algorithm != "argon2id" {
+        return Err(anyhow!("unsupported kdf {}", kdf.algorithm));
+    }
+    let params = Params::new(kdf.memory_kib, kdf.iterations, kdf.parallelism, Some(32))
+        .map_err(|e| anyhow!("{}", e))?;
+    let salt = STANDARD.decode(&kdf.salt)?;
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(master.as_bytes(), &salt, &mut key)
+        .map_err(|e| anyhow!("{}", e))?;
+    Ok(key)
+}
+
+// kdf 参数作为附加数据参与认证，改动文件头也会解密失败
+fn associated_data(kdf: &KdfParams) -> Result<Vec<u8>> {
+    Ok(serde_json::to_vec(&(VAULT_VERSION, kdf))?)
+}
+
+/// vault、导出文件、key 和 share 这类敏感文件只允许当前用户读写，返回前数据已经落盘
+pub fn write_private(path: impl AsRef<Path>, content: &[u8]) -> Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    // mode 只在新建文件时生效，覆盖已有文件时也要收紧权限
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(content)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vault_roundtrip() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("rcli-vault-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let path = dir.join("test.vault");
+        let _ = fs::remove_file(&path);
+
+        let mut vault = Vault::init(&path, "master")?;
+        vault.add(
+            "github",
+            VaultEntry::new("s3cret!".into(), Some("me".into()), None, None),
+            false,
+        )?;
+        assert!(vault
+            .add(
+                "github",
+                VaultEntry::new("x".into(), None, None, None),
+                false
+            )
+            .is_err());
+        vault.save()?;
+        assert!(Vault::init(&path, "master").is_err());
+
+        // 文件里不能出现明文
+        let content = fs::read_to_string(&path)?;
+        assert!(!content.contains("s3cret!") && !content.contains("github"));
+
+        let mut vault = Vault::open(&path, "master")?;
+        assert_eq!(vault.get("github")?.password, "s3cret!");
+        assert_eq!(vault.list().count(), 1);
+        vault.remove("github")?;
+        assert!(vault.get("github").is_err());
+
+        assert!(Vault::open(&path, "wrong").is_err());
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -1,4 +1,9 @@
-use anyhow::Result;
+use std::io::IsTerminal;
+
+use anyhow::{anyhow, Result};
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use rayon::{iter::IndexedParallelIterator, prelude::*, ThreadPoolBuilder};
 
 pub fn get_reader(input: &str) -> Result<Box<dyn std::io::Read>> {
     // `if` and `else` have incompatible types expected `Stdin`, found `File`
@@ -10,3 +15,139 @@ pub fn get_reader(input: &str) -> Result<Box<dyn std::io::Read>> {
     };
     Ok(reader)
 }
+
+/// 和 hexdump -C 一样的格式: 偏移量、每行 16 个字节的十六进制、可打印字符
+pub fn hexdump(data: &[u8]) -> String {
+    let mut ret = String::new();
+    for (i, line) in data.chunks(16).enumerate() {
+        let mut hex = String::with_capacity(49);
+        for (j, b) in line.iter().enumerate() {
+            if j == 8 {
+                hex.push(' ');
+            }
+            hex.push_str(&format!("{:02x} ", b));
+        }
+        let ascii: String = line
+            .iter()
+            .map(|b| match b {
+                0x20..=0x7e => *b as char,
+                _ => '.',
+            })
+            .collect();
+        ret.push_str(&format!("{:08x}  {:<49} |{}|\n", i * 16, hex, ascii));
+    }
+    ret.push_str(&format!("{:08x}\n", data.len()));
+    ret
+}
+
+/// 生成器使用的随机数来源: 默认是系统熵的 ThreadRng，指定 seed 时用 ChaCha20 得到可复现的结果
+/// 和 get_reader 一样装到 Box 里，调用方不用关心具体是哪一种 rng；是否提示不安全由调用方决定
+pub fn build_rng(seed: Option<u64>) -> Box<dyn RngCore> {
+    match seed {
+        Some(seed) => Box::new(ChaCha20Rng::seed_from_u64(seed)),
+        None => Box::new(rand::rng()),
+    }
+}
+
+/// 读取主密码之类的敏感输入: 指定文件时读文件，终端下不回显地提示输入，否则读 stdin 的第一行
+pub fn read_secret(prompt: &str, file: Option<&str>) -> Result<String> {
+    let secret = match file {
+        Some(file) => std::fs::read_to_string(file)?,
+        None if std::io::stdin().is_terminal() => rpassword::prompt_password(prompt)?,
+        None => {
+            let mut line = String::new();
+            std::io::stdin().read_line(&mut line)?;
+            line
+        }
+    };
+    let secret = secret.trim_end_matches(['\r', '\n']).to_string();
+    if secret.is_empty() {
+        return Err(anyhow!("secret must not be empty"));
+    }
+    Ok(secret)
+}
+
+/// 展开输入里的 glob 模式，普通路径原样保留
+pub fn expand_inputs(patterns: &[String]) -> Result<Vec<String>> {
+    let mut inputs = Vec::new();
+    for pattern in patterns {
+        if !pattern.contains(['*', '?', '[']) {
+            inputs.push(pattern.clone());
+            continue;
+        }
+        let before = inputs.len();
+        for path in glob::glob(pattern)? {
+            let path = path?;
+            if path.is_file() {
+                inputs.push(path.to_string_lossy().into_owned());
+            }
+        }
+        if inputs.len() == before {
+            return Err(anyhow!("no file matches {}", pattern));
+        }
+    }
+    Ok(inputs)
+}
+
+/// 把 items 分到 jobs 个线程上做 map，结果顺序和输入顺序一致
+/// jobs 为 1 时直接在当前线程顺序执行，为 0 时使用全部 CPU 核
+pub fn par_map<T, U, F>(jobs: usize, items: &[T], f: F) -> Result<Vec<U>>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(usize, &T) -> U + Sync,
+{
+    if jobs == 1 {
+        return Ok(items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| f(i, item))
+            .collect());
+    }
+
+    // par_iter().enumerate().map().collect() 会按原来的下标收集，不会打乱顺序
+    let pool = ThreadPoolBuilder::new().num_threads(jobs).build()?;
+    let ret = pool.install(|| {
+        items
+            .par_iter()
+            .enumerate()
+            .map(|(i, item)| f(i, item))
+            .collect()
+    });
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hexdump() {
+        let dump = hexdump(b"Hello, World!\n\x00\xff\x80");
+        assert_eq!(
+            dump,
+            "00000000  48 65 6c 6c 6f 2c 20 57  6f 72 6c 64 21 0a 00 ff  |Hello, World!...|\n\
+             00000010  80                                                |.|\n\
+             00000011\n"
+        );
+        assert_eq!(hexdump(b""), "00000000\n");
+    }
+
+    #[test]
+    fn test_expand_inputs() -> Result<()> {
+        let inputs = expand_inputs(&["assets/*.csv".into(), "Cargo.toml".into()])?;
+        assert_eq!(inputs, vec!["assets/juventus.csv", "Cargo.toml"]);
+        assert!(expand_inputs(&["assets/*.nope".into()]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_par_map_keeps_order() -> Result<()> {
+        let items = (0..1000).collect::<Vec<u32>>();
+        let expected = items.iter().map(|v| v * 2).collect::<Vec<_>>();
+        assert_eq!(par_map(1, &items, |_, v| v * 2)?, expected);
+        assert_eq!(par_map(4, &items, |_, v| v * 2)?, expected);
+        assert_eq!(par_map(4, &items, |i, _| i)?, (0..1000).collect::<Vec<_>>());
+        Ok(())
+    }
+}